@@ -1,93 +1,82 @@
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
 
-use aoc2021::day01;
-use aoc2021::day02;
-use aoc2021::day03;
-use aoc2021::day04;
-use aoc2021::day05;
-use aoc2021::day06;
-use aoc2021::day07;
-use aoc2021::day08;
-use aoc2021::day09;
-use aoc2021::day10;
-use aoc2021::day11;
-use aoc2021::day12;
-use aoc2021::day13;
-use aoc2021::day14;
-use aoc2021::day15;
-use aoc2021::day16;
-use aoc2021::day17;
-use aoc2021::day18;
-use aoc2021::day19;
-use aoc2021::day20;
-use aoc2021::day21;
-use aoc2021::day22;
-use aoc2021::day23;
-use aoc2021::day24;
-use aoc2021::day25;
+use aoc2021::{DynSolution, DAYS};
 
-#[allow(dead_code)]
-fn separate(c: &mut Criterion) {
-    c.bench_function("day01", |b| b.iter(|| black_box(day01::solve())));
-    c.bench_function("day02", |b| b.iter(|| black_box(day02::solve())));
-    c.bench_function("day03", |b| b.iter(|| black_box(day03::solve())));
-    c.bench_function("day04", |b| b.iter(|| black_box(day04::solve())));
-    c.bench_function("day05", |b| b.iter(|| black_box(day05::solve())));
-    c.bench_function("day06", |b| b.iter(|| black_box(day06::solve())));
-    c.bench_function("day07", |b| b.iter(|| black_box(day07::solve())));
-    c.bench_function("day08", |b| b.iter(|| black_box(day08::solve())));
-    c.bench_function("day09", |b| b.iter(|| black_box(day09::solve())));
-    c.bench_function("day10", |b| b.iter(|| black_box(day10::solve())));
-    c.bench_function("day11", |b| b.iter(|| black_box(day11::solve())));
-    c.bench_function("day12", |b| b.iter(|| black_box(day12::solve())));
-    c.bench_function("day13", |b| b.iter(|| black_box(day13::solve())));
-    c.bench_function("day14", |b| b.iter(|| black_box(day14::solve())));
-    c.bench_function("day15", |b| b.iter(|| black_box(day15::solve())));
-    c.bench_function("day16", |b| b.iter(|| black_box(day16::solve())));
-    c.bench_function("day17", |b| b.iter(|| black_box(day17::solve())));
-    c.bench_function("day18", |b| b.iter(|| black_box(day18::solve())));
-    c.bench_function("day19", |b| b.iter(|| black_box(day19::solve())));
-    c.bench_function("day20", |b| b.iter(|| black_box(day20::solve())));
-    c.bench_function("day21", |b| b.iter(|| black_box(day21::solve())));
-    c.bench_function("day23", |b| b.iter(|| black_box(day23::solve())));
-    c.bench_function("day24", |b| b.iter(|| black_box(day24::solve())));
-    c.bench_function("day25", |b| b.iter(|| black_box(day25::solve())));
+/// number of items (lines, fishes, crabs...) of the synthetic inputs
+const SIZES: [usize; 3] = [100, 1_000, 10_000];
+
+/// The real input, named after its size in bytes, followed by synthetic
+/// inputs for the days that have a generator.
+fn inputs(day: &dyn DynSolution) -> Vec<(String, String)> {
+    let real = day.input().to_string();
+    let mut inputs = vec![(format!("real-{}", real.len()), real)];
+    inputs.extend(
+        SIZES
+            .iter()
+            .filter_map(|&size| Some((size.to_string(), synthetic(day.day(), size)?))),
+    );
+    inputs
+}
+
+/// Days whose generator size is a number of lines or items, so that the
+/// inputs grow linearly with it.
+const LINEAR_DAYS: [u8; 7] = [1, 2, 3, 6, 7, 8, 10];
+
+fn synthetic(day: u8, size: usize) -> Option<String> {
+    if !LINEAR_DAYS.contains(&day) {
+        return None;
+    }
+    // seeded so that runs are comparable
+    aoc2021::gen::generate(day, size, 0x2021_1201)
+}
+
+/// `dayNN/parse`, `dayNN/part1` and `dayNN/part2` groups for every day, so
+/// that parsing and solving are measured independently.
+fn per_day(c: &mut Criterion) {
+    for day in DAYS {
+        let inputs = inputs(day);
+
+        let mut group = c.benchmark_group(format!("{}/parse", day.name()));
+        for (id, raw) in &inputs {
+            group.throughput(Throughput::Bytes(raw.len() as u64));
+            group.bench_with_input(BenchmarkId::from_parameter(id), raw.as_str(), |b, raw| {
+                b.iter(|| black_box(day.parse(raw).unwrap()))
+            });
+        }
+        group.finish();
+
+        let parsed = inputs
+            .iter()
+            .map(|(id, raw)| (id, day.parse(raw).unwrap()))
+            .collect::<Vec<_>>();
+        for part in [1, 2] {
+            let mut group = c.benchmark_group(format!("{}/part{}", day.name(), part));
+            for (id, input) in &parsed {
+                group.bench_with_input(
+                    BenchmarkId::from_parameter(id),
+                    input.as_ref(),
+                    |b, input| {
+                        b.iter(|| match part {
+                            1 => black_box(day.part1(input)),
+                            _ => black_box(day.part2(input)),
+                        })
+                    },
+                );
+            }
+            group.finish();
+        }
+    }
 }
 
-#[allow(dead_code)]
 fn all_at_once(c: &mut Criterion) {
     c.bench_function("all", |b| {
         b.iter(|| {
-            black_box({
-                day01::solve();
-                day02::solve();
-                day03::solve();
-                day04::solve();
-                day05::solve();
-                day06::solve();
-                day07::solve();
-                day08::solve();
-                day09::solve();
-                day10::solve();
-                day11::solve();
-                day12::solve();
-                day13::solve();
-                day14::solve();
-                day15::solve();
-                day16::solve();
-                day17::solve();
-                day18::solve();
-                day19::solve();
-                day20::solve();
-                day21::solve();
-                day22::solve();
-                day23::solve();
-                day24::solve();
-                day25::solve();
-            })
+            for day in DAYS {
+                black_box(day.solve().unwrap());
+            }
         })
     });
 }
 
-criterion_group!(benches, separate, all_at_once);
+criterion_group!(benches, per_day, all_at_once);
 criterion_main!(benches);
@@ -0,0 +1,149 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+/// whether the counting allocator is installed, with the `count-allocs`
+/// feature
+pub const ENABLED: bool = cfg!(feature = "count-allocs");
+
+#[cfg(feature = "count-allocs")]
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Allocations made while running some code.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Allocs {
+    /// calls to `alloc` and `realloc`
+    pub count: u64,
+    /// highest number of bytes allocated at once, on top of what was already
+    /// allocated before
+    pub peak: usize,
+}
+
+/// Wraps the system allocator to count the allocations of each thread, so
+/// that days running in parallel don't pollute each other.
+pub struct CountingAllocator;
+
+struct Counters {
+    count: Cell<u64>,
+    // can be negative when freeing memory allocated by another thread
+    current: Cell<isize>,
+    peak: Cell<isize>,
+}
+
+thread_local! {
+    // const and without destructor, so it never allocates
+    static COUNTERS: Counters = const {
+        Counters {
+            count: Cell::new(0),
+            current: Cell::new(0),
+            peak: Cell::new(0),
+        }
+    };
+}
+
+fn record(count: u64, bytes: isize) {
+    // fails during thread teardown, those allocations don't matter
+    let _ = COUNTERS.try_with(|c| {
+        c.count.set(c.count.get() + count);
+        let current = c.current.get() + bytes;
+        c.current.set(current);
+        c.peak.set(c.peak.get().max(current));
+    });
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            record(1, layout.size() as isize);
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            record(1, layout.size() as isize);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        record(0, -(layout.size() as isize));
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new = System.realloc(ptr, layout, new_size);
+        if !new.is_null() {
+            record(1, new_size as isize - layout.size() as isize);
+        }
+        new
+    }
+}
+
+/// Run `f` and count its allocations on the current thread. Returns `None`
+/// when the counting allocator isn't installed.
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, Option<Allocs>) {
+    if !ENABLED {
+        return (f(), None);
+    }
+
+    let (count, current, peak) = COUNTERS.with(|c| (c.count.get(), c.current.get(), c.peak.get()));
+    COUNTERS.with(|c| c.peak.set(current));
+    let result = f();
+    let allocs = COUNTERS.with(|c| {
+        let allocs = Allocs {
+            count: c.count.get() - count,
+            peak: (c.peak.get() - current).max(0) as usize,
+        };
+        // keep the peak of an enclosing measure correct
+        c.peak.set(c.peak.get().max(peak));
+        allocs
+    });
+    (result, Some(allocs))
+}
+
+/// `1.5KiB`, `12.0MiB`...
+pub fn fmt_bytes(bytes: usize) -> String {
+    let mut size = bytes as f64;
+    for unit in ["B", "KiB", "MiB"] {
+        if size < 1024.0 {
+            return match unit {
+                "B" => format!("{}B", bytes),
+                _ => format!("{:.1}{}", size, unit),
+            };
+        }
+        size /= 1024.0;
+    }
+    format!("{:.1}GiB", size)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_measure() {
+        let (v, allocs) = measure(|| {
+            let v = vec![0u8; 1000];
+            drop(vec![0u8; 10]);
+            v
+        });
+        assert_eq!(1000, v.len());
+        if ENABLED {
+            let allocs = allocs.unwrap();
+            assert_eq!(2, allocs.count);
+            assert_eq!(1010, allocs.peak);
+        } else {
+            assert_eq!(None, allocs);
+        }
+    }
+
+    #[test]
+    fn test_fmt_bytes() {
+        assert_eq!("12B", fmt_bytes(12));
+        assert_eq!("1.5KiB", fmt_bytes(1536));
+        assert_eq!("3.0MiB", fmt_bytes(3 << 20));
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::BTreeMap;
+
+use nom::branch::alt;
+use nom::bytes::complete::{escaped_transform, is_not, tag};
+use nom::character::complete::{self as character, char, not_line_ending, space0};
+use nom::combinator::{map, opt, value};
+use nom::sequence::{delimited, pair, preceded, separated_pair, terminated};
+use nom::IResult;
+
+use crate::error::ParseError;
+use crate::runner::Report;
+use crate::utils::parse_lines;
+
+/// Expected answers, stored in a small subset of toml:
+///
+/// ```toml
+/// [day01]
+/// part1 = "1832"
+/// part2 = "1858"
+/// ```
+///
+/// Strings support the `\n`, `\"` and `\\` escapes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Answers {
+    days: BTreeMap<u8, [Option<String>; 2]>,
+}
+
+/// the answers for the inputs in `resources/`
+pub const EMBEDDED: &str = include_str!("../answers.toml");
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Line {
+    Empty,
+    Day(u8),
+    Part(usize, String),
+}
+
+impl Answers {
+    pub fn parse(raw: &str) -> Result<Self, ParseError> {
+        let lines = parse_lines(
+            raw,
+            parse_line,
+            "a header like `[day01]` or a line like `part1 = \"42\"`",
+        )?;
+
+        let mut days = BTreeMap::new();
+        let mut current = None;
+        for (line, l) in raw.lines().zip(lines) {
+            match l {
+                Line::Empty => (),
+                Line::Day(day) => current = Some(day),
+                Line::Part(part, answer) => match current {
+                    Some(day) => {
+                        let answers: &mut [Option<String>; 2] = days.entry(day).or_default();
+                        answers[part - 1] = Some(answer);
+                    }
+                    None => return Err(ParseError::at(raw, line, "a header like `[day01]`")),
+                },
+            }
+        }
+        Ok(Self { days })
+    }
+
+    pub fn get(&self, day: u8, part: usize) -> Option<&str> {
+        self.days.get(&day)?.get(part.checked_sub(1)?)?.as_deref()
+    }
+
+    /// compare both answers of `report`, returning the parts that don't match
+    pub fn check(&self, report: &Report) -> Vec<Mismatch> {
+        [(1, &report.answer1), (2, &report.answer2)]
+            .into_iter()
+            .filter(|(part, actual)| self.get(report.day, *part) != Some(actual.as_str()))
+            .map(|(part, actual)| Mismatch {
+                day: report.day,
+                part,
+                expected: self.get(report.day, part).map(String::from),
+                actual: actual.clone(),
+            })
+            .collect()
+    }
+}
+
+/// An answer which is different from the expected one, or which has no
+/// expected value at all.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    pub day: u8,
+    pub part: usize,
+    pub expected: Option<String>,
+    pub actual: String,
+}
+
+impl std::fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "day{:02} part {}: ", self.day, self.part)?;
+        match &self.expected {
+            Some(expected) => write!(f, "expected {:?}, got {:?}", expected, self.actual),
+            None => write!(f, "no expected answer, got {:?}", self.actual),
+        }
+    }
+}
+
+fn parse_line(input: &str) -> IResult<&str, Line> {
+    let comment = opt(preceded(char('#'), not_line_ending));
+    terminated(
+        alt((
+            map(delimited(tag("[day"), character::u8, char(']')), Line::Day),
+            map(
+                separated_pair(
+                    preceded(tag("part"), alt((value(1, char('1')), value(2, char('2'))))),
+                    delimited(space0, char('='), space0),
+                    parse_string,
+                ),
+                |(part, answer)| Line::Part(part, answer),
+            ),
+            value(Line::Empty, space0),
+        )),
+        pair(space0, comment),
+    )(input)
+}
+
+fn parse_string(input: &str) -> IResult<&str, String> {
+    let escaped = escaped_transform(
+        is_not("\\\""),
+        '\\',
+        alt((
+            value("\\", char('\\')),
+            value("\"", char('"')),
+            value("\n", char('n')),
+        )),
+    );
+    delimited(
+        char('"'),
+        map(opt(escaped), Option::unwrap_or_default),
+        char('"'),
+    )(input)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TEST_INPUT: &str = r#"# a comment
+[day01]
+part1 = "1832"
+part2 = "a \"quoted\"\nanswer" # trailing comment
+
+[day13]
+part2 = ""
+"#;
+
+    #[test]
+    fn test_parse() {
+        let answers = Answers::parse(TEST_INPUT).unwrap();
+        assert_eq!(Some("1832"), answers.get(1, 1));
+        assert_eq!(Some("a \"quoted\"\nanswer"), answers.get(1, 2));
+        assert_eq!(None, answers.get(13, 1));
+        assert_eq!(Some(""), answers.get(13, 2));
+        assert_eq!(None, answers.get(2, 1));
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let err = Answers::parse("part1 = \"1\"\n").unwrap_err();
+        assert_eq!((1, 1), (err.line, err.column));
+        let err = Answers::parse("[day01]\npart3 = \"1\"\n").unwrap_err();
+        assert_eq!(2, err.line);
+    }
+
+    #[test]
+    fn test_embedded() {
+        let answers = Answers::parse(EMBEDDED).unwrap();
+        assert_eq!(Some("star!"), answers.get(25, 2));
+    }
+}
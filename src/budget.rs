@@ -0,0 +1,391 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::time::Duration;
+
+use nom::branch::alt;
+use nom::bytes::complete::{escaped_transform, is_not, tag, take};
+use nom::character::complete::{
+    self as character, anychar, char, multispace0, not_line_ending, space0,
+};
+use nom::combinator::{map, map_opt, opt, value};
+use nom::multi::separated_list0;
+use nom::number::complete::double;
+use nom::sequence::{delimited, pair, preceded, separated_pair, terminated};
+use nom::IResult;
+
+use crate::error::ParseError;
+use crate::runner::{fmt_duration, Report};
+use crate::utils::{parse_all, parse_lines};
+
+/// the budget of the inputs in `resources/`
+pub const EMBEDDED: &str = include_str!("../budget.toml");
+
+/// Maximum duration of each day, and of all of them, read from a file like
+/// `budget.toml`:
+///
+/// ```toml
+/// total = 1000
+/// tolerance = 20
+/// day01 = 1.5
+/// ```
+///
+/// Durations are in milliseconds, and the tolerance is a percentage.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Budget {
+    pub total: Option<Duration>,
+    /// how much slower than the baseline a day can be, in percent
+    pub tolerance: f64,
+    days: BTreeMap<u8, Duration>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Line {
+    Empty,
+    Total(f64),
+    Tolerance(f64),
+    Day(u8, f64),
+}
+
+impl Budget {
+    pub fn parse(raw: &str) -> Result<Self, ParseError> {
+        let lines = parse_lines(
+            raw,
+            parse_line,
+            "a line like `day01 = 1.5`, `total = 1000` or `tolerance = 20`",
+        )?;
+
+        let mut budget = Budget::default();
+        for line in lines {
+            match line {
+                Line::Empty => (),
+                Line::Total(ms) => budget.total = Some(from_ms(ms)),
+                Line::Tolerance(pct) => budget.tolerance = pct,
+                Line::Day(day, ms) => {
+                    budget.days.insert(day, from_ms(ms));
+                }
+            }
+        }
+        Ok(budget)
+    }
+
+    pub fn get(&self, day: u8) -> Option<Duration> {
+        self.days.get(&day).copied()
+    }
+}
+
+fn parse_line(input: &str) -> IResult<&str, Line> {
+    let comment = opt(preceded(char('#'), not_line_ending));
+    let key = |k| terminated(tag(k), delimited(space0, char('='), space0));
+    terminated(
+        alt((
+            map(preceded(key("total"), double), Line::Total),
+            map(preceded(key("tolerance"), double), Line::Tolerance),
+            map(
+                separated_pair(
+                    preceded(tag("day"), character::u8),
+                    delimited(space0, char('='), space0),
+                    double,
+                ),
+                |(day, ms)| Line::Day(day, ms),
+            ),
+            value(Line::Empty, space0),
+        )),
+        pair(space0, comment),
+    )(input)
+}
+
+fn from_ms(ms: f64) -> Duration {
+    Duration::from_secs_f64(ms.max(0.0) / 1000.0)
+}
+
+/// Median duration of each day, read back from the output of
+/// `aoc2021 --format json`.
+pub fn parse_baseline(raw: &str) -> Result<BTreeMap<u8, Duration>, ParseError> {
+    let expected = "the output of `aoc2021 --format json`";
+    let value = parse_all(raw, delimited(multispace0, json, multispace0), expected)?;
+
+    let mut days = BTreeMap::new();
+    let rows = match &value {
+        Json::Array(rows) => rows,
+        _ => return Err(ParseError::at(raw, raw.trim_start(), "an array")),
+    };
+    for row in rows {
+        let (day, part, parse_ms, median_ms) = match (
+            row.get("day"),
+            row.get("part"),
+            row.get("parse_ms"),
+            row.get("median_ms"),
+        ) {
+            (
+                Some(Json::Number(day)),
+                Some(Json::Number(part)),
+                Some(Json::Number(parse_ms)),
+                Some(Json::Number(median_ms)),
+            ) => (*day as u8, *part, *parse_ms, *median_ms),
+            _ => return Err(ParseError::eof(raw, expected)),
+        };
+        // the parse time is repeated for both parts
+        let ms = if part == 1.0 {
+            parse_ms + median_ms
+        } else {
+            median_ms
+        };
+        *days.entry(day).or_default() += from_ms(ms);
+    }
+    Ok(days)
+}
+
+/// Just enough json to read the output of the runner.
+#[derive(Debug, Clone, PartialEq)]
+enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+}
+
+fn json(input: &str) -> IResult<&str, Json> {
+    alt((
+        value(Json::Null, tag("null")),
+        value(Json::Bool(true), tag("true")),
+        value(Json::Bool(false), tag("false")),
+        map(double, Json::Number),
+        map(json_string, Json::String),
+        map(json_list('[', json, ']'), Json::Array),
+        map(
+            json_list(
+                '{',
+                separated_pair(
+                    json_string,
+                    delimited(multispace0, char(':'), multispace0),
+                    json,
+                ),
+                '}',
+            ),
+            Json::Object,
+        ),
+    ))(input)
+}
+
+fn json_list<'a, O>(
+    open: char,
+    item: impl FnMut(&'a str) -> IResult<&'a str, O>,
+    close: char,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>> {
+    delimited(
+        pair(char(open), multispace0),
+        separated_list0(delimited(multispace0, char(','), multispace0), item),
+        pair(multispace0, char(close)),
+    )
+}
+
+fn json_string(input: &str) -> IResult<&str, String> {
+    let escaped = escaped_transform(
+        is_not("\\\""),
+        '\\',
+        alt((
+            value('\\', char('\\')),
+            value('"', char('"')),
+            value('/', char('/')),
+            value('\n', char('n')),
+            value('\r', char('r')),
+            value('\t', char('t')),
+            map_opt(preceded(char('u'), take(4usize)), |hex: &str| {
+                u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
+            }),
+            anychar,
+        )),
+    );
+    delimited(
+        char('"'),
+        map(opt(escaped), Option::unwrap_or_default),
+        char('"'),
+    )(input)
+}
+
+/// One line of the comparison between the measures and the budget.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row {
+    /// `dayNN` or `total`
+    pub name: String,
+    pub median: Duration,
+    pub budget: Option<Duration>,
+    pub baseline: Option<Duration>,
+    pub over_budget: bool,
+    pub regressed: bool,
+}
+
+impl Row {
+    pub fn is_ok(&self) -> bool {
+        !self.over_budget && !self.regressed
+    }
+}
+
+/// Compare the median of every report with its budget and baseline, the
+/// last row being the total.
+pub fn check(
+    budget: &Budget,
+    baseline: Option<&BTreeMap<u8, Duration>>,
+    reports: &[Report],
+) -> Vec<Row> {
+    let tolerance = 1.0 + budget.tolerance / 100.0;
+    let make_row = |name, median, limit: Option<Duration>, base: Option<Duration>| Row {
+        name,
+        median,
+        budget: limit,
+        baseline: base,
+        over_budget: limit.is_some_and(|limit| median > limit),
+        regressed: base.is_some_and(|base| median > base.mul_f64(tolerance)),
+    };
+
+    let mut rows = reports
+        .iter()
+        .map(|report| {
+            make_row(
+                format!("day{:02}", report.day),
+                report.median(),
+                budget.get(report.day),
+                baseline.and_then(|b| b.get(&report.day).copied()),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let total_baseline = baseline.map(|b| {
+        reports
+            .iter()
+            .filter_map(|r| b.get(&r.day))
+            .sum::<Duration>()
+    });
+    rows.push(make_row(
+        "total".to_string(),
+        reports.iter().map(Report::median).sum(),
+        budget.total,
+        total_baseline,
+    ));
+    rows
+}
+
+/// Print `rows` as a table, with the difference relative to the budget and
+/// the baseline.
+pub fn write_table(out: &mut impl Write, rows: &[Row]) -> io::Result<()> {
+    let opt = |d: Option<Duration>| d.map_or_else(|| "-".to_string(), fmt_duration);
+    let diff = |median: Duration, d: Option<Duration>| match d {
+        Some(d) if !d.is_zero() => {
+            format!(
+                "{:+.1}%",
+                (median.as_secs_f64() / d.as_secs_f64() - 1.0) * 100.0
+            )
+        }
+        _ => "-".to_string(),
+    };
+
+    writeln!(
+        out,
+        "{:<6} {:>12} {:>12} {:>8} {:>12} {:>8}  status",
+        "", "median", "budget", "diff", "baseline", "diff"
+    )?;
+    for row in rows {
+        let status = match (row.over_budget, row.regressed) {
+            (false, false) => "ok",
+            (true, false) => "over budget",
+            (false, true) => "regressed",
+            (true, true) => "over budget, regressed",
+        };
+        writeln!(
+            out,
+            "{:<6} {:>12} {:>12} {:>8} {:>12} {:>8}  {}",
+            row.name,
+            fmt_duration(row.median),
+            opt(row.budget),
+            diff(row.median, row.budget),
+            opt(row.baseline),
+            diff(row.median, row.baseline),
+            status
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::runner::Phase;
+
+    fn report(day: u8, ms: u64) -> Report {
+        let phase = |ms| Phase {
+            min: Duration::from_millis(ms),
+            median: Duration::from_millis(ms),
+            allocs: None,
+        };
+        Report {
+            day,
+            answer1: String::new(),
+            answer2: String::new(),
+            runs: 1,
+            parse: phase(0),
+            part1: phase(ms),
+            part2: phase(0),
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let budget =
+            Budget::parse("# comment\ntotal = 10\n\nday01 = 1.5 # ms\ntolerance=5\n").unwrap();
+        assert_eq!(Some(Duration::from_millis(10)), budget.total);
+        assert_eq!(Some(Duration::from_micros(1500)), budget.get(1));
+        assert_eq!(None, budget.get(2));
+        assert_eq!(5.0, budget.tolerance);
+
+        let err = Budget::parse("day01 = 1\nday02 = fast\n").unwrap_err();
+        assert_eq!((2, 1), (err.line, err.column));
+        assert!(Budget::parse(EMBEDDED).is_ok());
+    }
+
+    #[test]
+    fn test_parse_baseline() {
+        let raw = r#"[
+  {"day": 1, "part": 1, "answer": "a \"b\"", "runs": 1, "parse_ms": 1.000, "min_ms": 2.000, "median_ms": 2.000},
+  {"day": 1, "part": 2, "answer": "\n", "runs": 1, "parse_ms": 1.000, "min_ms": 3.000, "median_ms": 4.000}
+]
+"#;
+        let baseline = parse_baseline(raw).unwrap();
+        assert_eq!(Some(&Duration::from_millis(7)), baseline.get(&1));
+        assert!(parse_baseline("{}").is_err());
+        assert!(parse_baseline("[{\"day\": 1}]").is_err());
+    }
+
+    #[test]
+    fn test_check() {
+        let budget = Budget::parse("total = 25\ntolerance = 20\nday01 = 10\nday02 = 10\n").unwrap();
+        let baseline = BTreeMap::from([
+            (1, Duration::from_millis(10)),
+            (2, Duration::from_millis(5)),
+        ]);
+        let rows = check(&budget, Some(&baseline), &[report(1, 11), report(2, 7)]);
+
+        assert_eq!(3, rows.len());
+        assert!(rows[0].over_budget && !rows[0].regressed);
+        assert!(!rows[1].over_budget && rows[1].regressed);
+        assert!(rows[2].is_ok());
+        assert_eq!(Some(Duration::from_millis(15)), rows[2].baseline);
+
+        let mut table = Vec::new();
+        write_table(&mut table, &rows).unwrap();
+        let table = String::from_utf8(table).unwrap();
+        assert!(table.contains(
+            "day02       7.000ms     10.000ms   -30.0%      5.000ms   +40.0%  regressed\n"
+        ));
+    }
+}
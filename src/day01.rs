@@ -1,22 +1,212 @@
-use itertools::Itertools;
+use std::collections::VecDeque;
 
-pub fn solve() -> (usize, usize) {
-    let depths: Vec<usize> = include_str!("../resources/day01.txt")
-        .lines()
-        .map(|i| i.parse().unwrap())
-        .collect();
+use nom::character::complete as character;
+use nom::combinator::map;
 
-    let count1 = itertools::zip(&depths, &depths[1..])
-        .filter(|(a, b)| b > a)
-        .count();
+use crate::utils::parse_lines;
+use crate::{ParseError, Solution};
 
-    let it: Vec<usize> = depths
-        .into_iter()
-        .tuple_windows()
-        .map(|(a, b, c)| a + b + c)
-        .collect();
+pub struct Day01;
 
-    let count2 = itertools::zip(&it, &it[1..]).filter(|(a, b)| b > a).count();
+impl Solution for Day01 {
+    const DAY: u8 = 1;
+    const INPUT: &'static str = include_str!("../resources/day01.txt");
 
-    (count1, count2)
+    type Input = Vec<usize>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(raw: &str) -> Result<Self::Input, ParseError> {
+        parse_lines(raw, map(character::u64, |d| d as usize), "a depth")
+    }
+
+    fn part1(depths: &Self::Input) -> usize {
+        analyze(depths.iter().copied(), 1, Aggregate::Sum).increases
+    }
+
+    fn part2(depths: &Self::Input) -> usize {
+        analyze(depths.iter().copied(), 3, Aggregate::Sum).increases
+    }
+}
+
+/// How the depths of a window are combined before comparing it to the
+/// previous one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Aggregate {
+    #[default]
+    Sum,
+    /// the sum divided by the size of the window, as a float
+    Mean,
+    Max,
+}
+
+/// Windows `first` to `last`, each one deeper than the one before.
+/// A window is identified by the position of its first depth.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Run {
+    pub first: usize,
+    pub last: usize,
+}
+
+impl Run {
+    /// number of increases in the run
+    pub fn len(&self) -> usize {
+        self.last - self.first
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Analysis {
+    pub windows: usize,
+    pub increases: usize,
+    pub decreases: usize,
+    pub plateaus: usize,
+    /// the first of the longest runs, none if the depth never increases
+    pub longest_run: Option<Run>,
+}
+
+/// Compare every sliding window of `window` depths to the previous one.
+/// The depths are only read once, and only the current window is kept in
+/// memory, so they can come straight from a huge file.
+///
+/// Panics if `window` is 0.
+pub fn analyze(
+    depths: impl IntoIterator<Item = usize>,
+    window: usize,
+    aggregate: Aggregate,
+) -> Analysis {
+    let mut analysis = Analysis::default();
+    let mut run_start = 0;
+    let mut previous = None;
+    for (i, value) in windows(depths, window, aggregate).enumerate() {
+        analysis.windows += 1;
+        match previous.map(|p: f64| value.total_cmp(&p)) {
+            Some(std::cmp::Ordering::Greater) => {
+                analysis.increases += 1;
+                if analysis.longest_run.is_none_or(|r| i - run_start > r.len()) {
+                    analysis.longest_run = Some(Run {
+                        first: run_start,
+                        last: i,
+                    });
+                }
+            }
+            Some(std::cmp::Ordering::Less) => {
+                analysis.decreases += 1;
+                run_start = i;
+            }
+            Some(std::cmp::Ordering::Equal) => {
+                analysis.plateaus += 1;
+                run_start = i;
+            }
+            None => (),
+        }
+        previous = Some(value);
+    }
+    analysis
+}
+
+/// The value compared for each window, see [`Aggregate`]. The sums and maxima
+/// are exact as long as they stay below 2^53.
+fn windows(
+    depths: impl IntoIterator<Item = usize>,
+    window: usize,
+    aggregate: Aggregate,
+) -> impl Iterator<Item = f64> {
+    assert!(window > 0, "windows need at least one depth");
+    let mut depths = depths.into_iter().enumerate();
+    let mut current = VecDeque::with_capacity(window);
+    let mut sum = 0;
+    // (position, depth) of the candidates for the max, decreasing depths
+    let mut maxima: VecDeque<(usize, usize)> = VecDeque::new();
+    std::iter::from_fn(move || loop {
+        let (i, depth) = depths.next()?;
+        match aggregate {
+            Aggregate::Sum | Aggregate::Mean => {
+                current.push_back(depth);
+                sum += depth;
+                if current.len() > window {
+                    sum -= current.pop_front().unwrap();
+                }
+            }
+            Aggregate::Max => {
+                while maxima.back().is_some_and(|&(_, d)| d <= depth) {
+                    maxima.pop_back();
+                }
+                maxima.push_back((i, depth));
+                if maxima[0].0 + window <= i {
+                    maxima.pop_front();
+                }
+            }
+        }
+        if i + 1 >= window {
+            return Some(match aggregate {
+                Aggregate::Sum => sum as f64,
+                Aggregate::Mean => sum as f64 / window as f64,
+                Aggregate::Max => maxima[0].1 as f64,
+            });
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const DEPTHS: [usize; 10] = [199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
+
+    #[test]
+    fn test_analyze() {
+        let analysis = analyze(DEPTHS, 1, Aggregate::Sum);
+        assert_eq!(
+            Analysis {
+                windows: 10,
+                increases: 7,
+                decreases: 2,
+                plateaus: 0,
+                longest_run: Some(Run { first: 0, last: 3 }),
+            },
+            analysis
+        );
+
+        let analysis = analyze(DEPTHS, 3, Aggregate::Mean);
+        assert_eq!(
+            (8, 5, 1, 1),
+            (
+                analysis.windows,
+                analysis.increases,
+                analysis.decreases,
+                analysis.plateaus
+            )
+        );
+        assert_eq!(Some(Run { first: 3, last: 7 }), analysis.longest_run);
+    }
+
+    #[test]
+    fn test_mean() {
+        let means = windows([1, 2, 4, 4], 2, Aggregate::Mean).collect::<Vec<_>>();
+        assert_eq!(vec![1.5, 3., 4.], means);
+    }
+
+    #[test]
+    fn test_max() {
+        let maxima = windows(DEPTHS, 3, Aggregate::Max).collect::<Vec<_>>();
+        assert_eq!(vec![208., 210., 210., 210., 240., 269., 269., 269.], maxima);
+        let analysis = analyze(DEPTHS, 3, Aggregate::Max);
+        assert_eq!(
+            (3, 0, 4),
+            (analysis.increases, analysis.decreases, analysis.plateaus)
+        );
+        assert_eq!(Some(Run { first: 3, last: 5 }), analysis.longest_run);
+    }
+
+    #[test]
+    fn test_too_short() {
+        assert_eq!(Analysis::default(), analyze(DEPTHS, 11, Aggregate::Sum));
+        let analysis = analyze([3, 2, 1], 1, Aggregate::Sum);
+        assert_eq!((2, None), (analysis.decreases, analysis.longest_run));
+    }
 }
@@ -1,52 +1,203 @@
-pub fn solve() -> (usize, usize) {
-    let final_sub = include_str!("../resources/day02.txt")
-        .split_terminator('\n')
-        .fold(Sub::default(), move_sub);
-    let result1 = final_sub.x * final_sub.depth;
-
-    let final_sub = include_str!("../resources/day02.txt")
-        .split_terminator('\n')
-        .fold(Sub::default(), move_sub2);
-    let result2 = final_sub.x * final_sub.depth;
-
-    (result1, result2)
-}
-
-fn move_sub(mut sub: Sub, raw_line: &str) -> Sub {
-    let (cmd, n) = parse_line(raw_line);
-    match cmd {
-        "forward" => sub.x += n,
-        "down" => sub.depth += n,
-        "up" => sub.depth -= n,
-        _ => unreachable!(),
-    }
-    sub
-}
-
-fn move_sub2(mut sub: Sub, raw_line: &str) -> Sub {
-    let (cmd, n) = parse_line(raw_line);
-    match cmd {
-        "down" => sub.aim += n,
-        "up" => sub.aim -= n,
-        "forward" => {
-            sub.x += n;
-            sub.depth += sub.aim * n;
+use std::str::FromStr;
+
+use nom::branch::alt;
+use nom::bytes::complete::tag;
+use nom::character::complete as character;
+use nom::combinator::{all_consuming, map, value};
+use nom::sequence::preceded;
+use nom::IResult;
+
+use crate::utils::parse_lines;
+use crate::{ParseError, Solution};
+
+pub struct Day02;
+
+impl Solution for Day02 {
+    const DAY: u8 = 2;
+    const INPUT: &'static str = include_str!("../resources/day02.txt");
+
+    type Input = Vec<Command>;
+    type Answer1 = i64;
+    type Answer2 = i64;
+
+    fn parse(raw: &str) -> Result<Self::Input, ParseError> {
+        parse_lines(raw, parse_command, EXPECTED)
+    }
+
+    fn part1(cmds: &Self::Input) -> i64 {
+        run(&Direct, cmds).product()
+    }
+
+    fn part2(cmds: &Self::Input) -> i64 {
+        run(&Aimed, cmds).product()
+    }
+}
+
+const EXPECTED: &str = "`forward`, `back`, `down` or `up` followed by a number, or `turn`";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    Forward(i64),
+    /// like forward, in reverse
+    Back(i64),
+    Down(i64),
+    Up(i64),
+    /// face the other way, swapping forward and back
+    Turn,
+}
+
+impl FromStr for Command {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        all_consuming(parse_command)(s)
+            .map(|(_, cmd)| cmd)
+            .map_err(|err| ParseError::from_nom(s, err, EXPECTED))
+    }
+}
+
+fn parse_command(raw_line: &str) -> IResult<&str, Command> {
+    let n = || map(character::u64, |n| n as i64);
+    alt((
+        map(preceded(tag("forward "), n()), Command::Forward),
+        map(preceded(tag("back "), n()), Command::Back),
+        map(preceded(tag("down "), n()), Command::Down),
+        map(preceded(tag("up "), n()), Command::Up),
+        value(Command::Turn, tag("turn")),
+    ))(raw_line)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub x: i64,
+    pub depth: i64,
+    /// only used by [`Aimed`]
+    pub aim: i64,
+    /// 1 when facing forward, -1 after a turn
+    pub heading: i64,
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Self {
+            x: 0,
+            depth: 0,
+            aim: 0,
+            heading: 1,
         }
-        _ => unreachable!(),
     }
-    sub
 }
 
-fn parse_line(raw_line: &str) -> (&str, usize) {
-    let mut words = raw_line.split(' ');
-    let cmd = words.next().unwrap();
-    let n = words.next().unwrap().parse::<usize>().unwrap();
-    (cmd, n)
+impl Position {
+    /// the answer of the puzzle
+    pub fn product(&self) -> i64 {
+        self.x * self.depth
+    }
+}
+
+/// How the submarine interprets the commands.
+pub trait MovementModel {
+    fn apply(&self, position: &mut Position, command: Command);
+}
+
+/// Part 1: down and up change the depth directly.
+pub struct Direct;
+
+impl MovementModel for Direct {
+    fn apply(&self, pos: &mut Position, command: Command) {
+        match command {
+            Command::Forward(n) => pos.x += pos.heading * n,
+            Command::Back(n) => pos.x -= pos.heading * n,
+            Command::Down(n) => pos.depth += n,
+            Command::Up(n) => pos.depth -= n,
+            Command::Turn => pos.heading = -pos.heading,
+        }
+    }
+}
+
+/// Part 2: down and up change the aim, and moving changes the depth
+/// according to it.
+pub struct Aimed;
+
+impl MovementModel for Aimed {
+    fn apply(&self, pos: &mut Position, command: Command) {
+        match command {
+            Command::Forward(n) => {
+                pos.x += pos.heading * n;
+                pos.depth += pos.aim * n;
+            }
+            Command::Back(n) => {
+                pos.x -= pos.heading * n;
+                pos.depth -= pos.aim * n;
+            }
+            Command::Down(n) => pos.aim += n,
+            Command::Up(n) => pos.aim -= n,
+            Command::Turn => pos.heading = -pos.heading,
+        }
+    }
+}
+
+/// The starting position, then the position after each command.
+pub fn trajectory<'a, M: MovementModel + ?Sized>(
+    model: &'a M,
+    commands: impl IntoIterator<Item = &'a Command> + 'a,
+) -> impl Iterator<Item = Position> + 'a {
+    let mut pos = Position::default();
+    std::iter::once(pos).chain(commands.into_iter().map(move |cmd| {
+        model.apply(&mut pos, *cmd);
+        pos
+    }))
 }
 
-#[derive(Default, Debug)]
-struct Sub {
-    x: usize,
-    depth: usize,
-    aim: usize,
+/// final position after all the commands
+pub fn run<M: MovementModel + ?Sized>(model: &M, commands: &[Command]) -> Position {
+    commands.iter().fold(Position::default(), |mut pos, cmd| {
+        model.apply(&mut pos, *cmd);
+        pos
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TEST_INPUT: &str = include_str!("../resources/examples/day02/example.txt");
+
+    #[test]
+    fn test_trajectory() {
+        let cmds = Day02::parse(TEST_INPUT).unwrap();
+        let depths = trajectory(&Aimed, &cmds)
+            .map(|p| (p.x, p.depth))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                (0, 0),
+                (5, 0),
+                (5, 0),
+                (13, 40),
+                (13, 40),
+                (13, 40),
+                (15, 60)
+            ],
+            depths
+        );
+        assert_eq!(Some(run(&Aimed, &cmds)), trajectory(&Aimed, &cmds).last());
+    }
+
+    #[test]
+    fn test_extra_commands() {
+        let cmds = Day02::parse("down 2\nforward 5\nturn\nforward 2\nback 1\n").unwrap();
+        let pos = run(&Direct, &cmds);
+        assert_eq!((4, 2, -1), (pos.x, pos.depth, pos.heading));
+        let pos = run(&Aimed, &cmds);
+        assert_eq!((4, 12), (pos.x, pos.depth));
+    }
+
+    #[test]
+    fn test_unknown_command() {
+        let err = Day02::parse("forward 5\nleft 3\n").unwrap_err();
+        assert_eq!((2, 1), (err.line, err.column));
+        assert_eq!(Ok(Command::Back(3)), "back 3".parse());
+        assert!("turn 3".parse::<Command>().is_err());
+    }
 }
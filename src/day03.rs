@@ -1,96 +1,359 @@
-#[allow(dead_code)]
-const TEST: &str = "00100
-11110
-10110
-10111
-10101
-01111
-00111
-11100
-10000
-11001
-00010
-01010";
-
-pub fn solve() -> (usize, usize) {
-    let input = include_str!("../resources/day03.txt");
-    let v0: Vec<(usize, usize)> = input
-        .split('\n')
-        .next()
-        .unwrap()
-        .chars()
-        .map(|_| (0, 0))
-        .collect();
-    let k = (1 << v0.len()) - 1;
-    let final_v = input.split_terminator('\n').fold(v0, f);
-    let gamma = to_n(&final_v);
-    let epsilon = !gamma & k;
-    let result1 = gamma * epsilon;
-
-    let result2 = solve2();
-    // println!("day03 part 1: {}\nday03 part 2: {}", result1, result2);
-    (result1, result2)
+use std::cmp::Ordering;
+
+use bitvec::prelude::*;
+
+use crate::utils::check_grid;
+use crate::{ParseError, Solution};
+
+pub struct Day03;
+
+impl Solution for Day03 {
+    const DAY: u8 = 3;
+    const INPUT: &'static str = include_str!("../resources/day03.txt");
+
+    type Input = Diagnostic;
+    type Answer1 = Number;
+    type Answer2 = Number;
+
+    fn parse(raw: &str) -> Result<Self::Input, ParseError> {
+        let width = match raw.lines().next() {
+            Some(l) if !l.is_empty() => l.len(),
+            _ => return Err(ParseError::at(raw, raw, "a binary number")),
+        };
+        let mut diagnostic = Diagnostic::new(width);
+        for l in raw.lines() {
+            if let Some((i, _)) = l.char_indices().find(|(_, c)| *c != '0' && *c != '1') {
+                return Err(ParseError::at(raw, &l[i..], "`0` or `1`"));
+            } else if l.len() != width {
+                let expected = format!("{} bits, like the first line", width);
+                return Err(ParseError::at(raw, &l[l.len().min(width)..], expected));
+            }
+            diagnostic.push(l.bytes().map(|b| b == b'1'));
+        }
+        Ok(diagnostic)
+    }
+
+    fn check(raw: &str) -> Vec<ParseError> {
+        check_grid(
+            raw,
+            raw.lines(),
+            None,
+            |c| c == '0' || c == '1',
+            "`0` or `1`",
+        )
+    }
+
+    fn part1(diagnostic: &Self::Input) -> Number {
+        let gamma = diagnostic.select_columns(Selection::GAMMA);
+        let epsilon = diagnostic.select_columns(Selection::EPSILON);
+        product(&gamma, &epsilon)
+    }
+
+    fn part2(diagnostic: &Self::Input) -> Number {
+        let o2 = diagnostic.rating(Selection::OXYGEN).expect("some reports");
+        let co2 = diagnostic.rating(Selection::CO2).expect("some reports");
+        product(o2, co2)
+    }
+}
+
+/// the answers, whatever the width of the reports
+fn product(a: &Bits, b: &Bits) -> Number {
+    Number::from_bits(a).mul(&Number::from_bits(b))
+}
+
+/// A non negative number of any size, displayed in decimal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Number {
+    /// least significant first, without trailing zeros
+    limbs: Vec<u32>,
+}
+
+impl Number {
+    pub fn from_bits(bits: &Bits) -> Self {
+        let mut limbs = vec![0; bits.len().div_ceil(32)];
+        for (i, bit) in bits.iter().by_val().rev().enumerate() {
+            limbs[i / 32] |= (bit as u32) << (i % 32);
+        }
+        Self::trimmed(limbs)
+    }
+
+    pub fn mul(&self, other: &Number) -> Number {
+        let mut limbs = vec![0; self.limbs.len() + other.limbs.len()];
+        for (i, a) in self.limbs.iter().enumerate() {
+            let mut carry = 0;
+            for (j, b) in other.limbs.iter().enumerate() {
+                let n = *a as u64 * *b as u64 + limbs[i + j] as u64 + carry;
+                limbs[i + j] = n as u32;
+                carry = n >> 32;
+            }
+            limbs[i + other.limbs.len()] = carry as u32;
+        }
+        Self::trimmed(limbs)
+    }
+
+    fn trimmed(mut limbs: Vec<u32>) -> Self {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        Self { limbs }
+    }
 }
 
-fn f(v: Vec<(usize, usize)>, s: &str) -> Vec<(usize, usize)> {
-    v.into_iter()
-        .zip(s.chars())
-        .map(|((zero, one), c)| match c {
-            '0' => (zero + 1, one),
-            '1' => (zero, one + 1),
-            _ => unreachable!(),
-        })
-        .collect()
+impl std::fmt::Display for Number {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // divide by 10^9 until nothing is left, the remainders are the
+        // decimal digits 9 by 9, least significant first
+        const CHUNK: u64 = 1_000_000_000;
+        let mut limbs = self.limbs.clone();
+        let mut chunks = Vec::new();
+        while !limbs.is_empty() {
+            let mut rem = 0;
+            for limb in limbs.iter_mut().rev() {
+                let n = rem << 32 | *limb as u64;
+                *limb = (n / CHUNK) as u32;
+                rem = n % CHUNK;
+            }
+            chunks.push(rem);
+            while limbs.last() == Some(&0) {
+                limbs.pop();
+            }
+        }
+        match chunks.split_last() {
+            None => write!(f, "0"),
+            Some((first, rest)) => {
+                write!(f, "{}", first)?;
+                rest.iter().rev().try_for_each(|c| write!(f, "{:09}", c))
+            }
+        }
+    }
 }
 
-fn to_n(v: &[(usize, usize)]) -> usize {
-    v.iter()
-        .map(|(zero, one)| if zero >= one { 0 } else { 1 })
-        .fold(0, |acc, d| acc * 2 + d)
+/// a report, or a number built from the columns, most significant bit first
+pub type Bits = BitSlice<Msb0, usize>;
+
+/// The value of `bits`, if it fits.
+pub fn to_number(bits: &Bits) -> Option<u64> {
+    let start = bits.first_one().unwrap_or(bits.len());
+    if bits.len() - start > 64 {
+        return None;
+    }
+    Some(
+        bits[start..]
+            .iter()
+            .by_val()
+            .fold(0, |n, b| n << 1 | b as u64),
+    )
 }
 
-fn solve2() -> usize {
-    let input = include_str!("../resources/day03.txt");
-    let nums: Vec<usize> = input
-        .split_terminator('\n')
-        .map(|l| usize::from_str_radix(l, 2).unwrap())
-        .collect();
+/// All the reports, of the same number of bits, which can be any.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    width: usize,
+    /// the reports one after the other
+    bits: BitVec<Msb0, usize>,
+}
 
-    let k: usize = input.split('\n').next().unwrap().chars().count();
-    let masks: Vec<usize> = (0..k).into_iter().map(|i| 1 << (k - i - 1)).collect();
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ColumnStats {
+    pub zeros: usize,
+    pub ones: usize,
+}
 
-    let o2_cmp = |a, b| a > b;
-    let co2_cmp = |a, b| a <= b;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Criterion {
+    MostCommon,
+    LeastCommon,
+}
 
-    let o2 = filter_2(&masks, &nums, o2_cmp);
-    let co2 = filter_2(&masks, &nums, co2_cmp);
-    o2 * co2
+/// Which bit to pick in a column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Selection {
+    pub criterion: Criterion,
+    /// the bit picked when there are as many zeros as ones
+    pub on_tie: bool,
 }
 
-fn filter_2<F>(masks: &[usize], nums: &[usize], f: F) -> usize
-where
-    F: Fn(usize, usize) -> bool,
-{
-    let mut candidates: Vec<usize> = nums.to_vec();
-    for m in masks {
-        let (zero, one) = {
-            let ones = &candidates.iter().filter(|n| *n & m > 0).count();
-            (candidates.len() - ones, *ones)
-        };
+impl Selection {
+    pub const GAMMA: Selection = Selection::new(Criterion::MostCommon, false);
+    pub const EPSILON: Selection = Selection::new(Criterion::LeastCommon, true);
+    pub const OXYGEN: Selection = Selection::new(Criterion::MostCommon, true);
+    pub const CO2: Selection = Selection::new(Criterion::LeastCommon, false);
+
+    pub const fn new(criterion: Criterion, on_tie: bool) -> Self {
+        Self { criterion, on_tie }
+    }
 
-        candidates = candidates
+    pub fn pick(&self, stats: ColumnStats) -> bool {
+        match (stats.ones.cmp(&stats.zeros), self.criterion) {
+            (Ordering::Equal, _) => self.on_tie,
+            (Ordering::Greater, Criterion::MostCommon)
+            | (Ordering::Less, Criterion::LeastCommon) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Diagnostic {
+    pub fn new(width: usize) -> Self {
+        Self {
+            width,
+            bits: BitVec::new(),
+        }
+    }
+
+    /// Panics if `report` is not `width` bits.
+    pub fn push(&mut self, report: impl IntoIterator<Item = bool>) {
+        let len = self.bits.len();
+        self.bits.extend(report);
+        assert_eq!(self.width, self.bits.len() - len, "bad report width");
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn len(&self) -> usize {
+        self.bits.len().checked_div(self.width).unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.is_empty()
+    }
+
+    pub fn report(&self, i: usize) -> &Bits {
+        &self.bits[i * self.width..(i + 1) * self.width]
+    }
+
+    /// zeros and ones of every column, from the most significant one
+    pub fn stats(&self) -> Vec<ColumnStats> {
+        let all = (0..self.len()).collect::<Vec<_>>();
+        (0..self.width).map(|col| self.count(col, &all)).collect()
+    }
+
+    /// The bit picked in every column, like the gamma and epsilon rates.
+    pub fn select_columns(&self, selection: Selection) -> BitVec<Msb0, usize> {
+        self.stats()
             .into_iter()
-            .filter(|n| {
-                if f(zero, one) {
-                    ((!*n) & m) > 0
-                } else {
-                    *n & m > 0
-                }
-            })
-            .collect();
-        if candidates.len() == 1 {
-            return *candidates.first().unwrap();
+            .map(|s| selection.pick(s))
+            .collect()
+    }
+
+    /// Keep the reports with the picked bit in each column in turn, until
+    /// only one is left, like the oxygen generator and CO2 scrubber ratings.
+    /// A column where all the reports left agree rules out none of them.
+    pub fn rating(&self, selection: Selection) -> Option<&Bits> {
+        let mut candidates = (0..self.len()).collect::<Vec<_>>();
+        for col in 0..self.width {
+            if candidates.len() <= 1 {
+                break;
+            }
+            let stats = self.count(col, &candidates);
+            if stats.zeros == 0 || stats.ones == 0 {
+                continue;
+            }
+            let bit = selection.pick(stats);
+            candidates.retain(|&i| self.report(i)[col] == bit);
         }
+        // several candidates can only be left if they are identical
+        candidates.first().map(|&i| self.report(i))
+    }
+
+    fn count(&self, col: usize, reports: &[usize]) -> ColumnStats {
+        let ones = reports.iter().filter(|&&i| self.report(i)[col]).count();
+        ColumnStats {
+            zeros: reports.len() - ones,
+            ones,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TEST_INPUT: &str = include_str!("../resources/examples/day03/example.txt");
+
+    fn bits(s: &str) -> BitVec<Msb0, usize> {
+        s.bytes().map(|b| b == b'1').collect()
+    }
+
+    #[test]
+    fn test_rates() {
+        let diagnostic = Day03::parse(TEST_INPUT).unwrap();
+        assert_eq!((12, 5), (diagnostic.len(), diagnostic.width()));
+        assert_eq!(ColumnStats { zeros: 5, ones: 7 }, diagnostic.stats()[0]);
+        assert_eq!(bits("10110"), diagnostic.select_columns(Selection::GAMMA));
+        assert_eq!(bits("01001"), diagnostic.select_columns(Selection::EPSILON));
+        assert_eq!(
+            Some(&bits("10111")[..]),
+            diagnostic.rating(Selection::OXYGEN)
+        );
+        assert_eq!(Some(&bits("01010")[..]), diagnostic.rating(Selection::CO2));
+    }
+
+    #[test]
+    fn test_filter_shared_bit() {
+        // 000 and 001 are left after the first bit, and both have a 0 next
+        let diagnostic = Day03::parse("000\n001\n110\n111\n100\n").unwrap();
+        assert_eq!(Some(&bits("111")[..]), diagnostic.rating(Selection::OXYGEN));
+        assert_eq!(Some(&bits("000")[..]), diagnostic.rating(Selection::CO2));
+    }
+
+    #[test]
+    fn test_tie_break() {
+        let diagnostic = Day03::parse("01\n10\n").unwrap();
+        let zero_first = Selection::new(Criterion::MostCommon, false);
+        assert_eq!(Some(&bits("01")[..]), diagnostic.rating(zero_first));
+        assert_eq!(Some(&bits("10")[..]), diagnostic.rating(Selection::OXYGEN));
+        assert_eq!(bits("00"), diagnostic.select_columns(zero_first));
+    }
+
+    #[test]
+    fn test_wide() {
+        let wide = format!(
+            "1{}\n0{}\n1{}\n",
+            "0".repeat(99),
+            "1".repeat(99),
+            "1".repeat(99)
+        );
+        let diagnostic = Day03::parse(&wide).unwrap();
+        let gamma = diagnostic.select_columns(Selection::GAMMA);
+        assert_eq!(100, gamma.len());
+        assert_eq!(None, to_number(&gamma));
+        let co2 = diagnostic.rating(Selection::CO2).unwrap();
+        assert_eq!(Some(u64::MAX), to_number(&co2[36..]));
+        assert_eq!(Some(1), to_number(&bits("0001")));
+
+        let wide = [
+            "10".repeat(50),
+            "01".repeat(50),
+            "1100".repeat(25),
+            "0110".repeat(25),
+            "1110".repeat(25),
+        ];
+        let diagnostic = Day03::parse(&(wide.join("\n") + "\n")).unwrap();
+        assert_eq!(
+            "99987256087226061589277641301070143193353117732076408908750",
+            Day03::part1(&diagnostic).to_string()
+        );
+        assert_eq!(
+            "499936280436130307946388206505350715966765588660382044543750",
+            Day03::part2(&diagnostic).to_string()
+        );
+    }
+
+    #[test]
+    fn test_number() {
+        let n = Number::from_bits(&bits(&format!("1{}", "0".repeat(64))));
+        assert_eq!("18446744073709551616", n.to_string());
+        assert_eq!(
+            "340282366920938463463374607431768211456",
+            n.mul(&n).to_string()
+        );
+        assert_eq!("0", Number::from_bits(&bits("000")).to_string());
+        assert_eq!(
+            "1000000000",
+            Number::from_bits(&bits("111011100110101100101000000000")).to_string()
+        );
     }
-    unreachable!();
 }
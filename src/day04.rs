@@ -1,107 +1,388 @@
+use arrayvec::ArrayVec;
 use itertools::Itertools;
 use nom::bytes::complete::tag;
-use nom::character::complete::{line_ending, space0, space1};
+use nom::character::complete::{self as character, space0, space1};
 use nom::combinator::all_consuming;
-use nom::multi::{count, separated_list1};
-use nom::sequence::{preceded, terminated, tuple};
-use std::collections::BTreeSet;
-
-pub fn solve() -> (usize, usize) {
-    let puzzle = parse_puzzle(include_str!("../resources/day04.txt"));
-    (solve1(&puzzle), solve2(&puzzle))
-}
-
-fn solve1(puzzle: &Puzzle) -> usize {
-    let mut drawn_nums: Vec<u8> = vec![];
-    for n in &puzzle.numbers {
-        drawn_nums.push(*n);
-        for grid in &puzzle.grids {
-            if grid.has_won(&drawn_nums) {
-                return get_magic_num(&drawn_nums, grid, *n);
-            }
+use nom::multi::separated_list1;
+use nom::sequence::preceded;
+
+use crate::{ParseError, Solution};
+
+pub struct Day04;
+
+impl Solution for Day04 {
+    const DAY: u8 = 4;
+    const INPUT: &'static str = include_str!("../resources/day04.txt");
+
+    type Input = Puzzle;
+    type Answer1 = Score;
+    type Answer2 = Score;
+
+    fn parse(raw: &str) -> Result<Self::Input, ParseError> {
+        parse_puzzle(raw)
+    }
+
+    fn check(raw: &str) -> Vec<ParseError> {
+        check_puzzle(raw)
+    }
+
+    fn part1(puzzle: &Self::Input) -> Self::Answer1 {
+        solve1(puzzle)
+    }
+
+    fn part2(puzzle: &Self::Input) -> Self::Answer2 {
+        solve2(puzzle)
+    }
+}
+
+fn solve1(puzzle: &Puzzle) -> Score {
+    let finishes = Bingo::new(&puzzle.grids).play(&puzzle.numbers);
+    Score(finishes.first().map(|f| f.score))
+}
+
+fn solve2(puzzle: &Puzzle) -> Score {
+    let finishes = Bingo::new(&puzzle.grids).play(&puzzle.numbers);
+    Score(finishes.last().map(|f| f.score))
+}
+
+/// The score of the first or last board to win, none of them may.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Score(pub Option<usize>);
+
+impl std::fmt::Display for Score {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            Some(score) => write!(f, "{}", score),
+            None => f.write_str("no board wins"),
         }
     }
-    unreachable!()
 }
 
-fn solve2(puzzle: &Puzzle) -> usize {
-    let mut drawn_nums = vec![];
-    let mut remaining_grids: BTreeSet<&Grid> = puzzle.grids.iter().collect();
-    for n in &puzzle.numbers {
-        drawn_nums.push(*n);
-        for grid in &puzzle.grids {
-            if grid.has_won(&drawn_nums) {
-                remaining_grids.remove(&grid);
-                if remaining_grids.is_empty() {
-                    return get_magic_num(&drawn_nums, grid, *n);
+/// A board completing a line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Finish {
+    /// index of the board in the puzzle
+    pub board: usize,
+    /// index of the winning draw
+    pub draw: usize,
+    pub number: u8,
+    /// sum of the unmarked numbers, times the winning one
+    pub score: usize,
+}
+
+/// Plays the draws on all the boards at once.
+#[derive(Debug)]
+pub struct Bingo<'a> {
+    grids: &'a [Grid],
+    diagonals: bool,
+    /// for every number, the boards and cells where it is
+    cells: Vec<Vec<(usize, usize)>>,
+}
+
+impl<'a> Bingo<'a> {
+    pub fn new(grids: &'a [Grid]) -> Self {
+        let mut cells = vec![Vec::new(); 256];
+        for (board, grid) in grids.iter().enumerate() {
+            for (cell, n) in grid.nums.iter().enumerate() {
+                cells[*n as usize].push((board, cell));
+            }
+        }
+        Self {
+            grids,
+            diagonals: false,
+            cells,
+        }
+    }
+
+    /// Also win with the two diagonals, on the square boards.
+    pub fn diagonals(mut self, diagonals: bool) -> Self {
+        self.diagonals = diagonals;
+        self
+    }
+
+    /// Every board winning with `draws`, in the order they do. Boards
+    /// winning on the same draw are in the puzzle order.
+    pub fn play(&self, draws: &[u8]) -> Vec<Finish> {
+        let mut boards = self
+            .grids
+            .iter()
+            .map(|grid| Board::new(grid, self.diagonals))
+            .collect::<Vec<_>>();
+        let mut finishes = Vec::new();
+        let mut winners = Vec::new();
+        for (draw, &number) in draws.iter().enumerate() {
+            // a board can have the number in several cells, all of them must
+            // be marked before scoring it. The cells are sorted by board.
+            for &(board, cell) in &self.cells[number as usize] {
+                let state = &mut boards[board];
+                if !state.won
+                    && state.mark(&self.grids[board], cell)
+                    && winners.last() != Some(&board)
+                {
+                    winners.push(board);
                 }
             }
+            for board in winners.drain(..) {
+                let state = &mut boards[board];
+                state.won = true;
+                finishes.push(Finish {
+                    board,
+                    draw,
+                    number,
+                    score: state.unmarked * number as usize,
+                });
+            }
         }
+        finishes
     }
-    unreachable!()
-}
 
-fn get_magic_num(drawn_nums: &[u8], grid: &Grid, n: u8) -> usize {
-    let s: usize = grid
-        .nums
-        .iter()
-        .filter_map(|k| {
-            if !drawn_nums.contains(k) {
-                Some(*k as usize)
-            } else {
-                None
+    /// The fewest draws after which `board` can win, when the numbers of
+    /// `draws` can be drawn in any order, along with such an order: the
+    /// numbers of its shortest line first, then the others as they were.
+    /// Other boards may win before or with it. None if no line of `board`
+    /// is entirely in `draws`.
+    pub fn fastest_win(&self, board: usize, draws: &[u8]) -> Option<Strategy> {
+        let mut available = [false; 256];
+        for n in draws {
+            available[*n as usize] = true;
+        }
+        let grid = &self.grids[board];
+        let line = grid
+            .lines(self.diagonals)
+            .into_iter()
+            .map(|cells| cells.iter().map(|c| grid.nums[*c]).unique().collect_vec())
+            .filter(|line| line.iter().all(|n| available[*n as usize]))
+            .min_by_key(|line| line.len())?;
+
+        let mut first = [false; 256];
+        for n in &line {
+            first[*n as usize] = true;
+        }
+        let mut order = line.clone();
+        for n in draws {
+            // the first occurrence of the line numbers is already drawn
+            if !std::mem::replace(&mut first[*n as usize], false) {
+                order.push(*n);
             }
+        }
+        Some(Strategy {
+            draws: line.len(),
+            order,
         })
-        .sum::<usize>();
-    s * (n as usize)
+    }
+
+    /// The boards that cannot win with `draws`, whatever their order: they
+    /// have no line made of drawn numbers only.
+    pub fn never_winning(&self, draws: &[u8]) -> Vec<usize> {
+        let mut won = vec![false; self.grids.len()];
+        for finish in self.play(draws) {
+            won[finish.board] = true;
+        }
+        (0..self.grids.len()).filter(|b| !won[*b]).collect()
+    }
 }
 
+/// A draw order making a board win as soon as possible.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Strategy {
+    /// how many numbers are drawn when the board wins
+    pub draws: usize,
+    /// a permutation of the draws
+    pub order: Vec<u8>,
+}
+
+/// the marks of a board during a game
 #[derive(Debug)]
-struct Puzzle {
-    numbers: Vec<u8>,
-    grids: Vec<Grid>,
-}
-
-fn parse_puzzle(input: &str) -> Puzzle {
-    let r: nom::IResult<_, _> = all_consuming(tuple((
-        terminated(
-            separated_list1(tag(","), nom::character::complete::u8),
-            line_ending,
-        ),
-        preceded(line_ending, separated_list1(line_ending, parse_grid)),
-    )))(input);
-    let (_rest, (numbers, grids)) = r.expect("valid parser");
-    Puzzle { numbers, grids }
-}
-
-fn parse_grid(input: &str) -> nom::IResult<&str, Grid> {
-    let parse_row = separated_list1(space1, preceded(space0, nom::character::complete::u8));
-    nom::combinator::map(
-        count(terminated(parse_row, line_ending), 5),
-        |nums: Vec<Vec<u8>>| {
-            let nums: Vec<_> = nums.into_iter().flatten().collect();
-            Grid {
-                nums: nums.try_into().expect("correct size"),
+struct Board {
+    marked: Vec<bool>,
+    /// marked cells on each row, then column, then diagonal
+    hits: Vec<usize>,
+    unmarked: usize,
+    diagonals: bool,
+    won: bool,
+}
+
+impl Board {
+    fn new(grid: &Grid, diagonals: bool) -> Self {
+        let diagonals = diagonals && grid.width == grid.height;
+        Self {
+            marked: vec![false; grid.nums.len()],
+            hits: vec![0; grid.width + grid.height + 2],
+            unmarked: grid.nums.iter().map(|n| *n as usize).sum(),
+            diagonals,
+            won: false,
+        }
+    }
+
+    /// mark a cell, true if that completes a line
+    fn mark(&mut self, grid: &Grid, cell: usize) -> bool {
+        if std::mem::replace(&mut self.marked[cell], true) {
+            return false;
+        }
+        self.unmarked -= grid.nums[cell] as usize;
+        let (x, y) = (cell % grid.width, cell / grid.width);
+        let mut lines = ArrayVec::<(usize, usize), 4>::new();
+        lines.push((y, grid.width));
+        lines.push((grid.height + x, grid.height));
+        if self.diagonals && x == y {
+            lines.push((grid.width + grid.height, grid.width));
+        }
+        if self.diagonals && x + y == grid.width - 1 {
+            lines.push((grid.width + grid.height + 1, grid.width));
+        }
+        lines.into_iter().fold(false, |won, (line, len)| {
+            self.hits[line] += 1;
+            won || self.hits[line] == len
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct Puzzle {
+    pub numbers: Vec<u8>,
+    pub grids: Vec<Grid>,
+}
+
+fn parse_puzzle(input: &str) -> Result<Puzzle, ParseError> {
+    let lines = input.lines().collect::<Vec<_>>();
+    let numbers = match lines.first() {
+        Some(&l) => {
+            all_consuming(separated_list1(tag(","), character::u8))(l)
+                .map_err(|err| ParseError::from_nom(input, err, "comma separated numbers"))?
+                .1
+        }
+        None => return Err(ParseError::eof(input, "the drawn numbers")),
+    };
+
+    let grids: Vec<_> = lines[1..]
+        .split(|l| l.is_empty())
+        .filter(|block| !block.is_empty())
+        .map(|block| parse_grid(input, block))
+        .collect::<Result<_, _>>()?;
+    if grids.is_empty() {
+        return Err(ParseError::eof(input, "at least one board"));
+    }
+    Ok(Puzzle { numbers, grids })
+}
+
+/// every malformed line and board, where `parse_puzzle` stops at the first
+fn check_puzzle(input: &str) -> Vec<ParseError> {
+    let lines = input.lines().collect::<Vec<_>>();
+    let mut errors = Vec::new();
+    match lines.first() {
+        Some(&l) => {
+            if let Err(err) = all_consuming(separated_list1(tag(","), character::u8))(l) {
+                errors.push(ParseError::from_nom(input, err, "comma separated numbers"));
             }
-        },
-    )(input)
+        }
+        None => return vec![ParseError::eof(input, "the drawn numbers")],
+    }
+
+    let boards = lines[1..]
+        .split(|l| l.is_empty())
+        .filter(|block| !block.is_empty())
+        .collect::<Vec<_>>();
+    if boards.is_empty() {
+        errors.push(ParseError::eof(input, "at least one board"));
+    }
+    for rows in boards {
+        let mut width = None;
+        for row in rows.iter().copied() {
+            let parse_row = preceded(space0, separated_list1(space1, character::u8));
+            match all_consuming(parse_row)(row) {
+                Ok((_, ns)) => match width {
+                    Some(w) if w != ns.len() => errors.push(same_width(input, row, w)),
+                    Some(_) => (),
+                    None => width = Some(ns.len()),
+                },
+                Err(err) => {
+                    errors.push(ParseError::from_nom(input, err, "space separated numbers"));
+                }
+            }
+        }
+    }
+    errors
+}
+
+fn same_width(input: &str, row: &str, width: usize) -> ParseError {
+    let expected = format!("{} numbers per row, like the first one", width);
+    ParseError::at(input, &row[row.len()..], expected)
+}
+
+// `rows` are the consecutive lines of one board, all as wide as the first
+fn parse_grid(input: &str, rows: &[&str]) -> Result<Grid, ParseError> {
+    let mut nums = Vec::new();
+    let mut width = 0;
+    for row in rows.iter().copied() {
+        let parse_row = preceded(space0, separated_list1(space1, character::u8));
+        let (_, ns) = all_consuming(parse_row)(row)
+            .map_err(|err| ParseError::from_nom(input, err, "space separated numbers"))?;
+        if nums.is_empty() {
+            width = ns.len();
+        } else if ns.len() != width {
+            return Err(same_width(input, row, width));
+        }
+        nums.extend(ns);
+    }
+    Ok(Grid::new(width, rows.len(), nums))
 }
 
-#[derive(Debug, Ord, PartialOrd, PartialEq, Eq)]
-struct Grid {
-    nums: [u8; 25],
+/// A board of `width` x `height` numbers, row by row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid {
+    width: usize,
+    height: usize,
+    nums: Vec<u8>,
 }
 
 impl Grid {
-    fn has_won(&self, drawn_nums: &[u8]) -> bool {
+    /// Panics if there are not `width` x `height` numbers.
+    pub fn new(width: usize, height: usize, nums: Vec<u8>) -> Self {
+        assert_eq!(width * height, nums.len(), "bad board size");
+        Self {
+            width,
+            height,
+            nums,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn nums(&self) -> &[u8] {
+        &self.nums
+    }
+
+    /// The cells of every row, then column, then diagonal if asked for and
+    /// the board is square.
+    pub fn lines(&self, diagonals: bool) -> Vec<Vec<usize>> {
+        let (w, h) = (self.width, self.height);
+        let mut lines = (0..h)
+            .map(|y| (0..w).map(|x| y * w + x).collect())
+            .chain((0..w).map(|x| (0..h).map(|y| y * w + x).collect()))
+            .collect::<Vec<Vec<_>>>();
+        if diagonals && w == h {
+            lines.push((0..w).map(|i| i * w + i).collect());
+            lines.push((0..w).map(|i| i * w + w - 1 - i).collect());
+        }
+        lines
+    }
+
+    /// whether a row or a column is all in `drawn_nums`
+    pub fn has_won(&self, drawn_nums: &[u8]) -> bool {
         self.has_won_manual(drawn_nums)
     }
 
     fn has_won_manual(&self, drawn_nums: &[u8]) -> bool {
-        for row in 0..5 {
+        for row in 0..self.height {
             let mut r = true;
-            for col in 0..5 {
-                let idx = row * 5 + col;
+            for col in 0..self.width {
+                let idx = row * self.width + col;
                 let hit = drawn_nums.contains(&self.nums[idx]);
                 r = r && hit;
                 if !r {
@@ -113,10 +394,10 @@ impl Grid {
             }
         }
 
-        for col in 0..5 {
+        for col in 0..self.width {
             let mut r = true;
-            for row in 0..5 {
-                let idx = row * 5 + col;
+            for row in 0..self.height {
+                let idx = row * self.width + col;
                 let hit = drawn_nums.contains(&self.nums[idx]);
                 r = r && hit;
                 if !r {
@@ -131,23 +412,24 @@ impl Grid {
         false
     }
 
-    // nicer, but slower version.
+    // nicer, but slower version, checked against the manual one in
+    // test_has_won_agree
     #[allow(dead_code)]
     fn has_won_iter(&self, drawn_nums: &[u8]) -> bool {
         let row = self
             .nums
             .iter()
-            .chunks(5)
+            .chunks(self.width)
             .into_iter()
             .any(|row| row.into_iter().all(|n| drawn_nums.contains(n)));
         if row {
             return true;
         }
-        (0..5).into_iter().any(|offset| {
+        (0..self.width).any(|offset| {
             self.nums
                 .iter()
                 .skip(offset)
-                .step_by(5)
+                .step_by(self.width)
                 .all(|n| drawn_nums.contains(n))
         })
     }
@@ -156,47 +438,209 @@ impl Grid {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::prop::{self, Rng};
 
-    const TEST: &str = "7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
-
-22 13 17 11  0
- 8  2 23  4 24
-21  9 14 16  7
- 6 10  3 18  5
- 1 12 20 15 19
-
- 3 15  0  2 22
- 9 18 13 17  5
-19  8  7 25 23
-20 11 10 24  4
-14 21 16 12  6
-
-14 21 17 24  4
-10 16 15  9 19
-18  8 23 26 20
-22 11 13  6  5
- 2  0 12  3  7
-";
+    const TEST: &str = include_str!("../resources/examples/day04/example.txt");
 
     #[test]
     fn test_solve1() {
-        assert_eq!(4512, solve1(&parse_puzzle(TEST)));
+        assert_eq!(Score(Some(4512)), solve1(&parse_puzzle(TEST).unwrap()));
     }
 
     #[test]
     fn test_solve2() {
-        assert_eq!(1924, solve2(&parse_puzzle(TEST)));
+        assert_eq!(Score(Some(1924)), solve2(&parse_puzzle(TEST).unwrap()));
     }
 
     #[test]
-    fn test_has_won() {
-        let grid = Grid {
-            nums: [
-                3, 15, 0, 2, 22, 9, 18, 13, 17, 5, 19, 8, 7, 25, 23, 20, 11, 10, 24, 4, 14, 21, 16,
-                12, 6,
+    fn test_no_winner() {
+        let puzzle = parse_puzzle("1,2\n\n1 3\n4 2\n").unwrap();
+        assert_eq!(
+            (Score(None), Score(None)),
+            (solve1(&puzzle), solve2(&puzzle))
+        );
+        assert_eq!("no board wins", Score(None).to_string());
+    }
+
+    #[test]
+    fn test_check() {
+        assert!(check_puzzle(TEST).is_empty());
+        let raw = "7,4,x\n\n1 2 3 4 5\n1 2 3 4\n1 2 3 4 5\n1 2 3 4 5\n";
+        let errors = check_puzzle(raw)
+            .into_iter()
+            .map(|e| (e.line, e.expected))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                (1, "comma separated numbers".to_string()),
+                (4, "5 numbers per row, like the first one".to_string()),
             ],
-        };
+            errors
+        );
+    }
+
+    #[test]
+    fn test_has_won() {
+        let nums = vec![
+            3, 15, 0, 2, 22, 9, 18, 13, 17, 5, 19, 8, 7, 25, 23, 20, 11, 10, 24, 4, 14, 21, 16, 12,
+            6,
+        ];
+        let grid = Grid::new(5, 5, nums);
         let drawns = vec![7, 4, 9, 5, 11, 17, 23, 2, 0, 14, 21, 24, 10, 16, 13];
         assert!(grid.has_won(&drawns[..]));
     }
+
+    #[test]
+    fn test_has_won_agree() {
+        let random_draw = |rng: &mut Rng| {
+            let mut nums = (0..100).collect::<Vec<u8>>();
+            rng.shuffle(&mut nums);
+            let grid = Grid::new(5, 5, nums[..25].to_vec());
+            // draw from numbers mostly on the board, so that some of them win
+            let mut drawn = nums[..40].to_vec();
+            rng.shuffle(&mut drawn);
+            drawn.truncate(rng.below(drawn.len() + 1));
+            (grid, drawn)
+        };
+        let manual = |(grid, drawn): &(Grid, Vec<u8>)| grid.has_won_manual(drawn);
+        let iter = |(grid, drawn): &(Grid, Vec<u8>)| grid.has_won_iter(drawn);
+        prop::agree(random_draw, &[("manual", &manual), ("iter", &iter)]);
+    }
+
+    #[test]
+    fn test_finish_order() {
+        let puzzle = parse_puzzle(TEST).unwrap();
+        let finishes = Bingo::new(&puzzle.grids).play(&puzzle.numbers);
+        let order = finishes
+            .iter()
+            .map(|f| (f.board, f.number, f.score))
+            .collect::<Vec<_>>();
+        assert_eq!(vec![(2, 24, 4512), (0, 16, 2192), (1, 13, 1924)], order);
+    }
+
+    #[test]
+    fn test_custom_boards() {
+        let puzzle = parse_puzzle("1,5,9,3\n\n1 2 3\n4 5 6\n7 8 9\n\n3 8\n5 7\n9 7\n").unwrap();
+        assert_eq!((2, 3), (puzzle.grids[1].width(), puzzle.grids[1].height()));
+        let plain = Bingo::new(&puzzle.grids).play(&puzzle.numbers);
+        assert_eq!(
+            vec![(1, 3)],
+            plain.iter().map(|f| (f.board, f.draw)).collect::<Vec<_>>()
+        );
+        let finishes = Bingo::new(&puzzle.grids)
+            .diagonals(true)
+            .play(&puzzle.numbers);
+        let order = finishes
+            .iter()
+            .map(|f| (f.board, f.draw, f.score))
+            .collect::<Vec<_>>();
+        // the first board wins with its diagonal, the second one has none
+        assert_eq!(vec![(0, 2, 9 * 30), (1, 3, 3 * 22)], order);
+    }
+
+    #[test]
+    fn test_repeated_number() {
+        // 4 completes the first row, and is also in the second one
+        let puzzle = parse_puzzle("1,4\n\n1 4\n4 7\n").unwrap();
+        let finishes = Bingo::new(&puzzle.grids).play(&puzzle.numbers);
+        assert_eq!(
+            vec![Finish {
+                board: 0,
+                draw: 1,
+                number: 4,
+                score: 7 * 4,
+            }],
+            finishes
+        );
+    }
+
+    #[test]
+    fn test_bingo_agree() {
+        let random_game = |rng: &mut Rng| {
+            let mut nums = (0..50).collect::<Vec<u8>>();
+            let grids = (0..4)
+                .map(|_| {
+                    rng.shuffle(&mut nums);
+                    Grid::new(4, 3, nums[..12].to_vec())
+                })
+                .collect::<Vec<_>>();
+            rng.shuffle(&mut nums);
+            (grids, nums[..30].to_vec())
+        };
+        // the draw where each board wins, if any
+        let engine = |(grids, draws): &(Vec<Grid>, Vec<u8>)| {
+            let mut wins = vec![None; grids.len()];
+            for finish in Bingo::new(grids).play(draws) {
+                wins[finish.board] = Some(finish.draw);
+            }
+            wins
+        };
+        let rescan = |(grids, draws): &(Vec<Grid>, Vec<u8>)| {
+            grids
+                .iter()
+                .map(|g| (0..draws.len()).find(|&i| g.has_won(&draws[..=i])))
+                .collect::<Vec<_>>()
+        };
+        prop::agree(random_game, &[("engine", &engine), ("rescan", &rescan)]);
+    }
+
+    #[test]
+    fn test_fastest_win() {
+        let puzzle = parse_puzzle(TEST).unwrap();
+        let bingo = Bingo::new(&puzzle.grids);
+        let strategy = bingo.fastest_win(0, &puzzle.numbers).unwrap();
+        assert_eq!(5, strategy.draws);
+        // the first row: 22 13 17 11 0
+        assert_eq!(vec![22, 13, 17, 11, 0], strategy.order[..5]);
+        let mut sorted = strategy.order.clone();
+        sorted.sort_unstable();
+        let mut numbers = puzzle.numbers.clone();
+        numbers.sort_unstable();
+        assert_eq!(numbers, sorted);
+        assert!(bingo.never_winning(&puzzle.numbers).is_empty());
+
+        // four numbers cannot fill a line of five
+        let draws = puzzle.numbers[..4].to_vec();
+        assert_eq!(None, bingo.fastest_win(0, &draws));
+        assert_eq!(vec![0, 1, 2], bingo.never_winning(&draws));
+    }
+
+    #[test]
+    fn test_fastest_win_agree() {
+        let random_game = |rng: &mut Rng| {
+            let mut nums = (0..30).collect::<Vec<u8>>();
+            rng.shuffle(&mut nums);
+            let grid = Grid::new(4, 3, nums[..12].to_vec());
+            rng.shuffle(&mut nums);
+            (grid, nums[..20].to_vec())
+        };
+        // the fewest draws, and whether the order wins with exactly these
+        let fastest = |(grid, draws): &(Grid, Vec<u8>)| {
+            let grids = [grid.clone()];
+            Bingo::new(&grids).fastest_win(0, draws).map(|s| {
+                let wins =
+                    grid.has_won(&s.order[..s.draws]) && !grid.has_won(&s.order[..s.draws - 1]);
+                (s.draws, wins)
+            })
+        };
+        // the shortest winning prefix of any subset of the draws
+        let brute = |(grid, draws): &(Grid, Vec<u8>)| {
+            let drawn = grid
+                .nums()
+                .iter()
+                .filter(|n| draws.contains(n))
+                .copied()
+                .collect_vec();
+            (1..=drawn.len())
+                .find(|&k| {
+                    drawn
+                        .iter()
+                        .copied()
+                        .combinations(k)
+                        .any(|c| grid.has_won(&c))
+                })
+                .map(|k| (k, true))
+        };
+        prop::agree(random_game, &[("fastest", &fastest), ("brute", &brute)]);
+    }
 }
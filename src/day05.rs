@@ -1,15 +1,46 @@
 use nom::bytes::complete::tag;
-use nom::character::complete::line_ending;
-use nom::combinator::all_consuming;
-use nom::combinator::{map, opt};
-use nom::multi::separated_list1;
-use nom::sequence::{separated_pair, terminated};
+use nom::combinator::{all_consuming, map};
+use nom::sequence::separated_pair;
 use std::cmp;
 use std::collections::BTreeMap;
 
-pub fn solve() -> (usize, usize) {
-    let puzzle = parse_puzzle(include_str!("../resources/day05.txt"));
-    (solve1(&puzzle), solve2(&puzzle))
+use crate::geom::Point2;
+use crate::utils::Grid;
+use crate::{ParseError, Solution};
+
+pub struct Day05;
+
+impl Solution for Day05 {
+    const DAY: u8 = 5;
+    const INPUT: &'static str = include_str!("../resources/day05.txt");
+
+    type Input = Vec<(Point, Point)>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(raw: &str) -> Result<Self::Input, ParseError> {
+        parse_puzzle(raw)
+    }
+
+    /// every bad line, not only the first one
+    fn check(raw: &str) -> Vec<ParseError> {
+        let mut errors = raw
+            .lines()
+            .filter_map(|line| parse_checked(raw, line).err())
+            .collect::<Vec<_>>();
+        if raw.lines().next().is_none() {
+            errors.push(ParseError::eof(raw, "at least one line"));
+        }
+        errors
+    }
+
+    fn part1(puzzle: &Self::Input) -> Self::Answer1 {
+        solve1(puzzle)
+    }
+
+    fn part2(puzzle: &Self::Input) -> Self::Answer2 {
+        solve2(puzzle)
+    }
 }
 
 fn solve1(puzzle: &[(Point, Point)]) -> usize {
@@ -32,12 +63,29 @@ fn solve2(puzzle: &[(Point, Point)]) -> usize {
     grid.count_intersections()
 }
 
-fn parse_puzzle(input: &str) -> Vec<(Point, Point)> {
-    let result: nom::IResult<&str, _> = all_consuming(terminated(
-        separated_list1(line_ending, parse_line),
-        opt(line_ending),
-    ))(input);
-    result.expect("correct parser").1
+fn parse_puzzle(input: &str) -> Result<Vec<(Point, Point)>, ParseError> {
+    let lines = input
+        .lines()
+        .map(|line| parse_checked(input, line))
+        .collect::<Result<Vec<_>, _>>()?;
+    if lines.is_empty() {
+        return Err(ParseError::eof(input, "at least one line"));
+    }
+    Ok(lines)
+}
+
+/// [`line`] only walks horizontal, vertical and 45° lines, the error for the
+/// others points at their end
+fn parse_checked(input: &str, line: &str) -> Result<(Point, Point), ParseError> {
+    let (_, (from, to)) = all_consuming(parse_line)(line)
+        .map_err(|err| ParseError::from_nom(input, err, "a line like `x1,y1 -> x2,y2`"))?;
+    let (dx, dy) = ((to.x - from.x).abs(), (to.y - from.y).abs());
+    if dx != 0 && dy != 0 && dx != dy {
+        let end = line.find(" -> ").map_or(line, |i| &line[i + 4..]);
+        let expected = "an end point on the same row, column or diagonal";
+        return Err(ParseError::at(input, end, expected));
+    }
+    Ok((from, to))
 }
 
 fn parse_line(input: &str) -> nom::IResult<&str, (Point, Point)> {
@@ -51,51 +99,30 @@ fn parse_point(input: &str) -> nom::IResult<&str, Point> {
             tag(","),
             nom::character::complete::i32,
         ),
-        |(x, y)| Point { x, y },
+        |(x, y)| Point2::new(x, y),
     )(input)
 }
 
-#[derive(Ord, PartialOrd, Eq, PartialEq, Debug)]
-struct Point {
-    // technically unsigned, but having i32 avoid some casting down the line
-    x: i32,
-    y: i32,
-}
+// technically unsigned, but having i32 avoid some casting down the line
+pub type Point = Point2<i32>;
 
-impl Point {
-    fn line_to(&self, to: &Point) -> PointLine {
-        let dx = match self.x.cmp(&to.x) {
-            cmp::Ordering::Less => 1,
-            cmp::Ordering::Equal => 0,
-            cmp::Ordering::Greater => -1,
-        };
-
-        let dy = match self.y.cmp(&to.y) {
-            cmp::Ordering::Less => 1,
-            cmp::Ordering::Equal => 0,
-            cmp::Ordering::Greater => -1,
-        };
-
-        PointLine {
-            should_stop: false,
-            dx,
-            dy,
-            x: self.x,
-            y: self.y,
-            final_x: to.x,
-            final_y: to.y,
-        }
+/// every point from `from` to `to` included, the line being horizontal,
+/// vertical or diagonal
+fn line(from: &Point, to: &Point) -> PointLine {
+    let step = Point2::new((to.x - from.x).signum(), (to.y - from.y).signum());
+    PointLine {
+        should_stop: false,
+        step,
+        current: *from,
+        end: *to,
     }
 }
 
 struct PointLine {
     should_stop: bool,
-    dx: i32,
-    dy: i32,
-    x: i32,
-    y: i32,
-    final_x: i32,
-    final_y: i32,
+    step: Point,
+    current: Point,
+    end: Point,
 }
 
 impl std::iter::Iterator for PointLine {
@@ -106,17 +133,13 @@ impl std::iter::Iterator for PointLine {
             return None;
         };
 
-        let p = Some(Point {
-            x: self.x,
-            y: self.y,
-        });
-        if (self.x == self.final_x) && (self.y == self.final_y) {
+        let p = self.current;
+        if p == self.end {
             self.should_stop = true;
         }
-        self.x += self.dx;
-        self.y += self.dy;
+        self.current += self.step;
 
-        p
+        Some(p)
     }
 }
 
@@ -126,7 +149,9 @@ trait Aoc {
 }
 
 // Keep this around for posterity. It works, but the implementation
-// using a vector as the underlying storage is 94% faster
+// using a vector as the underlying storage is 94% faster.
+// test_grids_agree checks that both give the same results
+#[allow(dead_code)]
 #[derive(Default)]
 struct HashmapGrid {
     points: BTreeMap<Point, u32>,
@@ -134,7 +159,7 @@ struct HashmapGrid {
 
 impl Aoc for HashmapGrid {
     fn set_line(&mut self, from: &Point, to: &Point) {
-        for p in from.line_to(to) {
+        for p in line(from, to) {
             let e = self.points.entry(p).or_default();
             *e += 1;
         }
@@ -153,7 +178,7 @@ impl std::fmt::Debug for HashmapGrid {
             for x in 0..=max_x {
                 let val = self
                     .points
-                    .get(&Point { x, y })
+                    .get(&Point2::new(x, y))
                     .map(|n| format!("{}", n))
                     .unwrap_or_else(|| ".".to_string());
                 f.write_str(&val)?;
@@ -164,11 +189,7 @@ impl std::fmt::Debug for HashmapGrid {
     }
 }
 
-struct VecGrid {
-    max_x: usize,
-    max_y: usize,
-    points: Vec<u32>,
-}
+struct VecGrid(Grid<u32>);
 
 impl VecGrid {
     fn from_lines(puzzle: &[(Point, Point)]) -> Self {
@@ -183,83 +204,102 @@ impl VecGrid {
             .max()
             .expect("at least one line");
 
-        let n = (max_x + 1) * (max_y + 1);
-        Self {
-            max_x: max_x as _,
-            max_y: max_y as _,
-            points: vec![0; n as usize],
-        }
+        Self(Grid::new(max_x as usize + 1, max_y as usize + 1, 0))
     }
 }
 
 impl std::fmt::Debug for VecGrid {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
-        for y in 0..=self.max_y {
-            for x in 0..=self.max_x {
-                let idx = y * self.max_x + x;
-                let val = self
-                    .points
-                    .get(idx)
-                    .map(|n| {
-                        if *n == 0 {
-                            ".".to_string()
-                        } else {
-                            format!("{}", n)
-                        }
-                    })
-                    .unwrap();
-                f.write_str(&val)?;
-            }
-            f.write_str("\n")?;
-        }
-        Ok(())
+        let cells = self.0.map(|n| match n {
+            0 => ".".to_string(),
+            n => n.to_string(),
+        });
+        write!(f, "{}", cells)
     }
 }
 
 impl Aoc for VecGrid {
     fn set_line(&mut self, from: &Point, to: &Point) {
-        for p in from.line_to(to) {
-            let idx = p.y as usize * self.max_x + p.x as usize;
-            let e = self.points.get_mut(idx).unwrap();
-            *e += 1
+        for p in line(from, to) {
+            self.0[Point2::new(p.x as usize, p.y as usize)] += 1;
         }
     }
 
     fn count_intersections(&self) -> usize {
-        self.points.iter().filter(|v| **v > 1).count()
+        self.0.points.iter().filter(|v| **v > 1).count()
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::prop::{self, Rng};
 
-    const TEST_INPUT: &str = "0,9 -> 5,9
-8,0 -> 0,8
-9,4 -> 3,4
-2,2 -> 2,1
-7,0 -> 7,4
-6,4 -> 2,0
-0,9 -> 2,9
-3,4 -> 1,4
-0,0 -> 8,8
-5,5 -> 8,2";
+    const TEST_INPUT: &str = include_str!("../resources/examples/day05/example.txt");
 
     #[test]
     fn test_parser() {
         assert_eq!(
-            Ok(("", (Point { x: 8, y: 0 }, Point { x: 0, y: 8 }))),
+            Ok(("", (Point2::new(8, 0), Point2::new(0, 8)))),
             parse_line("8,0 -> 0,8")
         );
     }
 
     #[test]
     fn test_solve1() {
-        assert_eq!(5, solve1(&parse_puzzle(TEST_INPUT)));
+        assert_eq!(5, solve1(&parse_puzzle(TEST_INPUT).unwrap()));
     }
 
     #[test]
     fn test_solve2() {
-        assert_eq!(12, solve2(&parse_puzzle(TEST_INPUT)));
+        assert_eq!(12, solve2(&parse_puzzle(TEST_INPUT).unwrap()));
+    }
+
+    #[test]
+    fn test_check() {
+        assert!(Day05::check(TEST_INPUT).is_empty());
+        let raw = "0,0 -> 2,2\n0,0 -> 2,1\n1,1 -> 1,x\n3,1 -> 9,2\n";
+        let errors = Day05::check(raw);
+        assert_eq!(3, errors.len(), "{:?}", errors);
+        assert_eq!((2, 8), (errors[0].line, errors[0].column));
+        assert_eq!((4, 8), (errors[2].line, errors[2].column));
+        assert_eq!(Some(errors[0].clone()), parse_puzzle(raw).err());
+    }
+
+    fn random_lines(rng: &mut Rng) -> Vec<(Point, Point)> {
+        (0..rng.below(20) + 1)
+            .map(|_| {
+                let from = Point2::new(rng.between(0, 20), rng.between(0, 20));
+                let len = rng.between(0, 20 - from.x.max(from.y));
+                let to = match rng.below(3) {
+                    0 => Point2::new(from.x + len, from.y),
+                    1 => Point2::new(from.x, from.y + len),
+                    _ => Point2::new(from.x + len, from.y + len),
+                };
+                // lines can go either way
+                if rng.below(2) == 0 {
+                    (from, to)
+                } else {
+                    (to, from)
+                }
+            })
+            .collect()
+    }
+
+    fn count_intersections(mut grid: impl Aoc, lines: &[(Point, Point)]) -> usize {
+        for (from, to) in lines {
+            grid.set_line(from, to);
+        }
+        grid.count_intersections()
+    }
+
+    #[test]
+    fn test_grids_agree() {
+        let hashmap = |lines: &Vec<_>| count_intersections(HashmapGrid::default(), lines);
+        let vec = |lines: &Vec<_>| count_intersections(VecGrid::from_lines(lines), lines);
+        prop::agree(
+            random_lines,
+            &[("HashmapGrid", &hashmap), ("VecGrid", &vec)],
+        );
     }
 }
@@ -1,60 +1,104 @@
-pub fn solve() -> (usize, usize) {
-    let puzzle = include_str!("../resources/day06.txt");
-    (solve1(puzzle), solve2(puzzle))
+use nom::character::complete as character;
+use nom::multi::separated_list1;
+
+use crate::trace;
+use crate::utils::parse_all;
+use crate::{ParseError, Solution};
+
+pub struct Day06;
+
+impl Solution for Day06 {
+    const DAY: u8 = 6;
+    const INPUT: &'static str = include_str!("../resources/day06.txt");
+
+    type Input = Fishes;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(raw: &str) -> Result<Self::Input, ParseError> {
+        Fishes::from_input(raw)
+    }
+
+    fn part1(puzzle: &Self::Input) -> Self::Answer1 {
+        solve1(puzzle)
+    }
+
+    fn part2(puzzle: &Self::Input) -> Self::Answer2 {
+        solve2(puzzle)
+    }
 }
 
-fn solve1(input: &str) -> usize {
-    solve_n(input, 80)
+fn solve1(fishes: &Fishes) -> usize {
+    solve_n(fishes, 80)
 }
 
-fn solve2(input: &str) -> usize {
-    solve_n(input, 256)
+fn solve2(fishes: &Fishes) -> usize {
+    solve_n(fishes, 256)
 }
 
-fn solve_n(input: &str, limit: usize) -> usize {
-    let mut fishes = Fishes::from_input(input);
-    for _ in 0..limit {
-        fishes.next_gen()
+fn solve_n(fishes: &Fishes, limit: usize) -> usize {
+    let mut fishes = fishes.clone();
+    trace::step(0, || &fishes);
+    for i in 1..=limit {
+        fishes.next_gen();
+        trace::step(i, || &fishes);
     }
     fishes.states.iter().sum()
 }
 
-struct Fishes {
+#[derive(Clone)]
+pub struct Fishes {
     states: [usize; 9],
 }
 
 impl Fishes {
-    fn from_input(input: &str) -> Self {
+    fn from_input(input: &str) -> Result<Self, ParseError> {
+        let timers = parse_all(
+            input.trim_end(),
+            separated_list1(character::char(','), character::one_of("012345678")),
+            "comma separated timers, from 0 to 8",
+        )?;
         let mut states = [0; 9];
-        for c in input.chars().filter(|c| c.is_numeric()) {
+        for c in timers {
             let n = char::to_digit(c, 10).unwrap() as usize;
             states[n] += 1;
         }
-        Self { states }
+        Ok(Self { states })
     }
 
     fn next_gen(&mut self) {
         let to_spawn = self.states[0];
         for i in 0..8 {
-            self.states[i] = self.states[i+1]
+            self.states[i] = self.states[i + 1]
         }
         self.states[6] += to_spawn;
         self.states[8] = to_spawn;
     }
 }
 
+impl std::fmt::Display for Fishes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let total = self.states.iter().sum::<usize>();
+        write!(f, "{} fishes, by timer:", total)?;
+        for count in self.states {
+            write!(f, " {}", count)?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
-    const TEST: &str = "3,4,3,1,2";
+    const TEST: &str = include_str!("../resources/examples/day06/example.txt");
 
     #[test]
     fn test_solve1() {
-        assert_eq!(5934, solve1(TEST))
+        assert_eq!(5934, solve1(&Fishes::from_input(TEST).unwrap()))
     }
 
     #[test]
     fn test_solve2() {
-        assert_eq!(26984457539, solve2(TEST))
+        assert_eq!(26984457539, solve2(&Fishes::from_input(TEST).unwrap()))
     }
 }
@@ -1,12 +1,36 @@
-pub fn solve() -> (usize, usize) {
-    let puzzle = parse_puzzle(include_str!("../resources/day07.txt"));
-    (solve1(&puzzle), solve2(&puzzle))
+use nom::character::complete as character;
+use nom::combinator::map;
+use nom::multi::separated_list1;
+
+use crate::utils::parse_all;
+use crate::{ParseError, Solution};
+
+pub struct Day07;
+
+impl Solution for Day07 {
+    const DAY: u8 = 7;
+    const INPUT: &'static str = include_str!("../resources/day07.txt");
+
+    type Input = Vec<i32>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(raw: &str) -> Result<Self::Input, ParseError> {
+        parse_puzzle(raw)
+    }
+
+    fn part1(puzzle: &Self::Input) -> Self::Answer1 {
+        solve1(puzzle)
+    }
+
+    fn part2(puzzle: &Self::Input) -> Self::Answer2 {
+        solve2(puzzle)
+    }
 }
 
 fn solve1(input: &[i32]) -> usize {
     let m = input.iter().max().unwrap();
     (0..=*m)
-        .into_iter()
         .map(|pos| {
             let fuel: i32 = input.iter().map(|p| (p - pos).abs()).sum();
             fuel
@@ -18,7 +42,6 @@ fn solve1(input: &[i32]) -> usize {
 fn solve2(input: &[i32]) -> usize {
     let m = input.iter().max().unwrap();
     (0..=*m)
-        .into_iter()
         .map(|pos| {
             let fuel: i32 = input
                 .iter()
@@ -33,29 +56,27 @@ fn solve2(input: &[i32]) -> usize {
         .unwrap() as _
 }
 
-fn parse_puzzle(input: &str) -> Vec<i32> {
-    input
-        .split_terminator(',')
-        .map(|x| {
-            let x = x.strip_suffix('\n').unwrap_or(x);
-            x.parse::<i32>().unwrap()
-        })
-        .collect()
+fn parse_puzzle(input: &str) -> Result<Vec<i32>, ParseError> {
+    parse_all(
+        input.trim_end(),
+        separated_list1(character::char(','), map(character::u16, |n| n as i32)),
+        "comma separated positions",
+    )
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
-    const TEST: &str = "16,1,2,0,4,2,7,1,2,14";
+    const TEST: &str = include_str!("../resources/examples/day07/example.txt");
 
     #[test]
     fn test_solve1() {
-        assert_eq!(solve1(&parse_puzzle(TEST)[..]), 37)
+        assert_eq!(solve1(&parse_puzzle(TEST).unwrap()[..]), 37)
     }
 
     #[test]
     fn test_solve2() {
-        assert_eq!(solve2(&parse_puzzle(TEST)[..]), 168)
+        assert_eq!(solve2(&parse_puzzle(TEST).unwrap()[..]), 168)
     }
 }
@@ -1,6 +1,34 @@
-pub fn solve() -> (usize, usize) {
-    let lines = parse_puzzle(include_str!("../resources/day08.txt"));
-    (solve1(&lines), solve2(&lines))
+use nom::bytes::complete::is_a;
+use nom::character::complete as character;
+use nom::combinator::map;
+use nom::multi::count;
+use nom::sequence::{preceded, separated_pair, terminated};
+use nom::IResult;
+
+use crate::utils::parse_lines;
+use crate::{ParseError, Solution};
+
+pub struct Day08;
+
+impl Solution for Day08 {
+    const DAY: u8 = 8;
+    const INPUT: &'static str = include_str!("../resources/day08.txt");
+
+    type Input = Vec<Line>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(raw: &str) -> Result<Self::Input, ParseError> {
+        parse_puzzle(raw)
+    }
+
+    fn part1(lines: &Self::Input) -> Self::Answer1 {
+        solve1(lines)
+    }
+
+    fn part2(lines: &Self::Input) -> Self::Answer2 {
+        solve2(lines)
+    }
 }
 
 fn solve1(lines: &[Line]) -> usize {
@@ -74,39 +102,28 @@ fn decode_entry(line: &Line) -> usize {
 }
 
 #[derive(Debug)]
-struct Line {
+pub struct Line {
     input: [u8; 10],
     output: [u8; 4],
 }
 
-fn parse_puzzle(raw: &str) -> Vec<Line> {
-    raw.split_terminator('\n')
-        .map(parse_line)
-        .collect()
+fn parse_puzzle(raw: &str) -> Result<Vec<Line>, ParseError> {
+    parse_lines(raw, parse_line, "10 patterns, `|` and 4 digits")
 }
 
-fn parse_line(l: &str) -> Line {
-    let mut ls = l.split('|');
-    let input = ls
-        .next()
-        .unwrap()
-        .split_terminator(' ')
-        .map(parse_word)
-        .filter(|w| w > &0)
-        .collect::<Vec<_>>()
-        .try_into()
-        .unwrap();
-    let output = ls
-        .next()
-        .unwrap()
-        .split_terminator(' ')
-        .map(parse_word)
-        .filter(|w| w > &0)
-        .collect::<Vec<_>>()
-        .try_into()
-        .unwrap();
-
-    Line { input, output }
+fn parse_line(l: &str) -> IResult<&str, Line> {
+    let word = || map(is_a("abcdefg"), parse_word);
+    map(
+        separated_pair(
+            count(terminated(word(), character::char(' ')), 10),
+            character::char('|'),
+            count(preceded(character::char(' '), word()), 4),
+        ),
+        |(input, output)| Line {
+            input: input.try_into().unwrap(),
+            output: output.try_into().unwrap(),
+        },
+    )(l)
 }
 
 fn parse_word(w: &str) -> u8 {
@@ -130,22 +147,11 @@ fn parse_word(w: &str) -> u8 {
 mod test {
     use super::*;
 
-    const TEST_INPUT: &str =
-        "be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cefbgd gcbe
-edbfga begcd cbg gc gcadebf fbgde acbgfd abcde gfcbed gfec | fcgedb cgb dgebacf gc
-fgaebd cg bdaec gdafb agbcfd gdcbef bgcad gfac gcb cdgabef | cg cg fdcagb cbg
-fbegcd cbd adcefb dageb afcb bc aefdc ecdab fgdeca fcdbega | efabcd cedba gadfec cb
-aecbfdg fbg gf bafeg dbefa fcge gcbea fcaegb dgceab fcbdga | gecf egdcabf bgf bfgea
-fgeab ca afcebg bdacfeg cfaedg gcfdb baec bfadeg bafgc acf | gebdcfa ecba ca fadegcb
-dbcfg fgd bdegcaf fgec aegbdf ecdfab fbedc dacgb gdcebf gf | cefg dcbef fcge gbcadfe
-bdfegc cbegaf gecbf dfcage bdacg ed bedf ced adcbefg gebcd | ed bcgafe cdgba cbgef
-egadfb cdbfeg cegd fecab cgb gbdefca cg fgcdab egfdb bfceg | gbdfcae bgc cg cgb
-gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce
-";
+    const TEST_INPUT: &str = include_str!("../resources/examples/day08/example.txt");
 
     #[test]
     fn test_solve1() {
-        assert_eq!(26, solve1(&parse_puzzle(TEST_INPUT)))
+        assert_eq!(26, solve1(&parse_puzzle(TEST_INPUT).unwrap()))
     }
 
     #[test]
@@ -156,7 +162,7 @@ gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce
     #[test]
     fn test_parse_line() {
         let input = "be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cefbgd gcbe";
-        let l = &parse_puzzle(input)[0];
+        let l = &parse_puzzle(input).unwrap()[0];
         assert_eq!(l.output[0], 127)
     }
 
@@ -164,12 +170,14 @@ gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce
     fn test_decode_entry() {
         let line = parse_line(
             "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf",
-        );
+        )
+        .unwrap()
+        .1;
         assert_eq!(decode_entry(&line), 5353)
     }
 
     #[test]
     fn test_solve2() {
-        assert_eq!(61229, solve2(&parse_puzzle(TEST_INPUT)))
+        assert_eq!(61229, solve2(&parse_puzzle(TEST_INPUT).unwrap()))
     }
 }
@@ -2,57 +2,98 @@ use std::collections::BTreeSet;
 
 use itertools::Itertools;
 
-pub fn solve() -> (usize, usize) {
-    let grid = parse_grid(include_str!("../resources/day09.txt"));
-    (solve1(&grid), solve2(&grid))
+use crate::geom::Point2;
+use crate::render::{Image, Rgb};
+use crate::utils::{check_grid, Point};
+use crate::{ParseError, Solution};
+
+pub struct Day09;
+
+impl Solution for Day09 {
+    const DAY: u8 = 9;
+    const INPUT: &'static str = include_str!("../resources/day09.txt");
+
+    type Input = Grid;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(raw: &str) -> Result<Self::Input, ParseError> {
+        parse_grid(raw)
+    }
+
+    fn check(raw: &str) -> Vec<ParseError> {
+        check_grid(raw, raw.lines(), None, |c| c.is_ascii_digit(), "a digit")
+    }
+
+    fn render(grid: &Self::Input) -> Option<Image> {
+        Some(basins_image(grid))
+    }
+
+    fn part1(grid: &Self::Input) -> Self::Answer1 {
+        solve1(grid)
+    }
+
+    fn part2(grid: &Self::Input) -> Self::Answer2 {
+        solve2(grid)
+    }
 }
 
 fn solve1(grid: &Grid) -> usize {
-    grid.low_points()
-        .map(|(x, y)| *grid.get(x, y).unwrap() as usize + 1)
-        .sum()
+    grid.low_points().map(|p| grid[p] as usize + 1).sum()
 }
 
 fn solve2(grid: &Grid) -> usize {
     let mut bassins_len = grid
         .low_points()
-        .map(|(x, y)| grid.bassin_coords(x, y).len())
+        .map(|p| grid.bassin_coords(p.x, p.y).len())
         .collect::<Vec<_>>();
 
     bassins_len.sort_unstable();
     bassins_len.iter().rev().take(3).product()
 }
 
+/// every basin in its own colour, the 9s in black between them, and the low
+/// points in white
+fn basins_image(grid: &Grid) -> Image {
+    let mut image = grid.map(|_| Rgb::BLACK);
+    for (i, low) in grid.low_points().enumerate() {
+        for p in grid.bassin_coords(low.x, low.y) {
+            image[p] = Rgb::palette(i);
+        }
+        image[low] = Rgb::WHITE;
+    }
+    image
+}
+
 type Grid = crate::utils::Grid<u8>;
 
 impl Grid {
-    fn low_points(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+    /// a cell without neighbours, alone in its grid, is a low point too
+    fn low_points(&self) -> impl Iterator<Item = Point> + '_ {
         (0..self.height)
             .cartesian_product(0..self.width)
             .filter_map(|(y, x)| {
                 let val = self.get(x, y).unwrap();
                 if self
                     .neighbours(x, y)
-                    .filter_map(|(x, y)| self.get(x, y))
+                    .filter_map(|n| self.get(n.x, n.y))
                     .min()
-                    .unwrap()
-                    > val
+                    .is_none_or(|min| min > val)
                 {
-                    Some((x, y))
+                    Some(Point2::new(x, y))
                 } else {
                     None
                 }
             })
     }
 
-    fn bassin_coords(&self, x: usize, y: usize) -> BTreeSet<(usize, usize)> {
+    fn bassin_coords(&self, x: usize, y: usize) -> BTreeSet<Point> {
         let mut seen = BTreeSet::new();
-        let mut to_check = vec![(x, y)];
-        while let Some((x, y)) = to_check.pop() {
-            seen.insert((x, y));
-            for n in self.neighbours(x, y) {
-                let (nx, ny) = n;
-                if !seen.contains(&n) && (self.get(nx, ny).copied() != Some(9)) {
+        let mut to_check = vec![Point2::new(x, y)];
+        while let Some(p) = to_check.pop() {
+            seen.insert(p);
+            for n in self.neighbours(p.x, p.y) {
+                if !seen.contains(&n) && self[n] != 9 {
                     to_check.push(n)
                 }
             }
@@ -61,39 +102,24 @@ impl Grid {
     }
 }
 
-fn parse_grid(raw: &str) -> Grid {
-    let width = raw.split_terminator('\n').next().unwrap().len();
-    let height = raw.split_terminator('\n').count();
-    let points = raw
-        .chars()
-        .filter_map(|c| c.to_digit(10).map(|d| d as _))
-        .collect();
-    Grid {
-        points,
-        width,
-        height,
-    }
+fn parse_grid(raw: &str) -> Result<Grid, ParseError> {
+    Grid::from_digits(raw)
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
-    const TEST_INPUT: &str = "2199943210
-3987894921
-9856789892
-8767896789
-9899965678
-";
+    const TEST_INPUT: &str = include_str!("../resources/examples/day09/example.txt");
 
     #[test]
     fn test_solve1() {
-        assert_eq!(15, solve1(&parse_grid(TEST_INPUT)))
+        assert_eq!(15, solve1(&parse_grid(TEST_INPUT).unwrap()))
     }
 
     #[test]
     fn test_bassin_coords() {
-        let grid = parse_grid(TEST_INPUT);
+        let grid = parse_grid(TEST_INPUT).unwrap();
         assert_eq!(3, grid.bassin_coords(0, 0).len(), "top left");
         assert_eq!(9, grid.bassin_coords(9, 0).len(), "top right");
         assert_eq!(14, grid.bassin_coords(2, 2).len(), "middle");
@@ -102,7 +128,13 @@ mod test {
 
     #[test]
     fn test_solve2() {
-        let grid = parse_grid(TEST_INPUT);
+        let grid = parse_grid(TEST_INPUT).unwrap();
         assert_eq!(1134, solve2(&grid));
     }
+
+    #[test]
+    fn test_single_cell() {
+        let grid = parse_grid("5\n").unwrap();
+        assert_eq!((6, 1), (solve1(&grid), solve2(&grid)));
+    }
 }
@@ -1,6 +1,30 @@
-pub fn solve() -> (usize, usize) {
-    let puzzle = parse_puzzle(include_str!("../resources/day10.txt"));
-    (solve1(&puzzle), solve2(&puzzle))
+use nom::bytes::complete::is_a;
+use nom::combinator::map;
+
+use crate::utils::parse_lines;
+use crate::{ParseError, Solution};
+
+pub struct Day10;
+
+impl Solution for Day10 {
+    const DAY: u8 = 10;
+    const INPUT: &'static str = include_str!("../resources/day10.txt");
+
+    type Input = Vec<Vec<char>>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(raw: &str) -> Result<Self::Input, ParseError> {
+        parse_puzzle(raw)
+    }
+
+    fn part1(puzzle: &Self::Input) -> Self::Answer1 {
+        solve1(puzzle)
+    }
+
+    fn part2(puzzle: &Self::Input) -> Self::Answer2 {
+        solve2(puzzle)
+    }
 }
 
 fn solve1(puzzle: &[Vec<char>]) -> usize {
@@ -19,6 +43,7 @@ fn solve1(puzzle: &[Vec<char>]) -> usize {
         .sum()
 }
 
+/// the middle score of the incomplete lines, 0 when they are all corrupted
 fn solve2(puzzle: &[Vec<char>]) -> usize {
     let mut scores: Vec<_> = puzzle
         .iter()
@@ -28,7 +53,7 @@ fn solve2(puzzle: &[Vec<char>]) -> usize {
         })
         .collect();
     scores.sort_unstable();
-    scores[scores.len() / 2]
+    scores.get(scores.len() / 2).copied().unwrap_or(0)
 }
 
 #[derive(Debug, Default, PartialEq, Eq)]
@@ -84,27 +109,23 @@ fn check_line(line: &[char]) -> LineStatus {
     }
 }
 
-fn parse_puzzle(raw: &str) -> Vec<Vec<char>> {
-    raw.split_terminator('\n')
-        .map(|l| l.chars().collect())
-        .collect()
+fn parse_puzzle(raw: &str) -> Result<Vec<Vec<char>>, ParseError> {
+    let lines = parse_lines(
+        raw,
+        map(is_a("()[]{}<>"), |l: &str| l.chars().collect()),
+        "one of `()[]{}<>`",
+    )?;
+    if lines.is_empty() {
+        return Err(ParseError::eof(raw, "one of `()[]{}<>`"));
+    }
+    Ok(lines)
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
-    const TEST_INPUT: &str = "[({(<(())[]>[[{[]{<()<>>
-[(()[<>])]({[<{<<[]>>(
-{([(<{}[<>[]}>{[]{[(<()>
-(((({<>}<{<{<>}{[]{[]{}
-[[<[([]))<([[{}[[()]]]
-[{[{({}]{}}([{[{{{}}([]
-{<[[]]>}<{[{[{[]{()[[[]
-[<(<(<(<{}))><([]([]()
-<{([([[(<>()){}]>(<<{{
-<{([{{}}[<[[[<>{}]]]>[]]
-";
+    const TEST_INPUT: &str = include_str!("../resources/examples/day10/example.txt");
 
     #[test]
     fn test_check_line() {
@@ -114,7 +135,7 @@ mod test {
 
     #[test]
     fn test_solve1() {
-        assert_eq!(26397, solve1(&parse_puzzle(TEST_INPUT)));
+        assert_eq!(26397, solve1(&parse_puzzle(TEST_INPUT).unwrap()));
     }
 
     #[test]
@@ -129,6 +150,13 @@ mod test {
 
     #[test]
     fn test_solve2() {
-        assert_eq!(288957, solve2(&parse_puzzle(TEST_INPUT)));
+        assert_eq!(288957, solve2(&parse_puzzle(TEST_INPUT).unwrap()));
+    }
+
+    #[test]
+    fn test_all_corrupted() {
+        assert!(parse_puzzle("").is_err());
+        let puzzle = parse_puzzle("(]\n{()()()>\n").unwrap();
+        assert_eq!((57 + 25137, 0), (solve1(&puzzle), solve2(&puzzle)));
     }
 }
@@ -1,136 +1,147 @@
-use itertools::Itertools;
+use crate::render::{Image, Render};
+use crate::trace;
+use crate::utils::{check_grid, Point};
+use crate::{ParseError, Solution};
 
-pub fn solve() -> (usize, usize) {
-    let grid = parse(include_str!("../resources/day11.txt"));
-    (solve1(&grid), solve2(&grid))
-}
+pub struct Day11;
 
-fn solve1(grid: &Grid) -> usize {
-    let mut c = 0;
-    let mut g = *grid;
-    for _ in 0..100 {
-        let (flashed, g2) = next_grid(g);
-        g = g2;
-        c += flashed;
+impl Solution for Day11 {
+    const DAY: u8 = 11;
+    const INPUT: &'static str = include_str!("../resources/day11.txt");
+
+    type Input = Grid;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(raw: &str) -> Result<Self::Input, ParseError> {
+        parse(raw)
     }
-    c
-}
 
-fn solve2(grid: &Grid) -> usize {
-    let mut step = 0;
-    let mut grid = *grid;
-    loop {
-        if grid.iter().all(|&c| c == 0) {
-            break;
+    /// the solver works on any size, but the puzzle is about 10x10 octopuses
+    fn check(raw: &str) -> Vec<ParseError> {
+        match check_grid(
+            raw,
+            raw.lines(),
+            Some((10, 10)),
+            |c| c.is_ascii_digit(),
+            "a digit",
+        ) {
+            errors if errors.is_empty() => Self::parse(raw).err().into_iter().collect(),
+            errors => errors,
         }
-        let (_, g2) = next_grid(grid);
-        grid = g2;
-        step += 1;
     }
-    step
+
+    fn render(grid: &Self::Input) -> Option<Image> {
+        Some(grid.render())
+    }
+
+    fn part1(grid: &Self::Input) -> Self::Answer1 {
+        solve1(grid)
+    }
+
+    fn part2(grid: &Self::Input) -> Self::Answer2 {
+        solve2(grid)
+    }
 }
 
-type Grid = [u8; 100];
+fn solve1(grid: &Grid) -> usize {
+    let mut grid = grid.clone();
+    trace::frame(0, || &grid);
+    (1..=100)
+        .map(|i| {
+            let flashes = step(&mut grid);
+            trace::frame(i, || &grid);
+            flashes
+        })
+        .sum()
+}
 
-fn parse(raw: &str) -> Grid {
-    raw.chars()
-        .filter_map(|c| c.to_digit(10).map(|d| d as u8))
-        .collect::<Vec<_>>()
-        .try_into()
-        .unwrap()
+fn solve2(grid: &Grid) -> usize {
+    steps_to_sync(grid, MAX_STEPS).expect("checked when parsing")
 }
 
-fn neighbours(x: usize, y: usize) -> Vec<(usize, usize)> {
-    let mut ns = Vec::with_capacity(9);
-    for dx in [x.checked_sub(1), Some(x), x.checked_add(1)] {
-        for dy in [y.checked_sub(1), Some(y), y.checked_add(1)] {
-            if let Some(px) = dx {
-                if let Some(py) = dy {
-                    if (px != x || py != y) && px < 10 && py < 10 {
-                        ns.push((px, py))
-                    }
-                }
-            }
+/// grids of any size are accepted, but some never synchronise: give up on
+/// them after this many steps
+const MAX_STEPS: usize = 10_000;
+
+/// how many steps until all the octopuses flash at once, if they do within
+/// `max` steps
+pub(crate) fn steps_to_sync(grid: &Grid, max: usize) -> Option<usize> {
+    let mut grid = grid.clone();
+    for step_count in 0..max {
+        trace::frame(step_count, || &grid);
+        if grid.points.iter().all(|&c| c == 0) {
+            return Some(step_count);
         }
+        step(&mut grid);
     }
-    ns
+    None
 }
 
-fn next_grid(mut grid: Grid) -> (usize, Grid) {
-    let mut flashed = Vec::new();
-    let mut to_visit: Vec<(usize, usize)> = (0..10).cartesian_product(0..10).collect();
-
-    while let Some((x, y)) = to_visit.pop() {
-        let idx = y * 10 + x;
-        grid[idx] += 1;
-        if grid[idx] > 9 && !flashed.contains(&idx) {
-            flashed.push(idx);
-            for (x2, y2) in neighbours(x, y) {
-                to_visit.push((x2, y2));
-            }
-        }
-    }
+pub(crate) type Grid = crate::utils::Grid<u8>;
 
-    let flash_count = flashed.len();
-    for idx in flashed {
-        grid[idx] = 0;
+fn parse(raw: &str) -> Result<Grid, ParseError> {
+    let grid = Grid::from_digits(raw)?;
+    match steps_to_sync(&grid, MAX_STEPS) {
+        Some(_) => Ok(grid),
+        None => Err(ParseError::eof(
+            raw,
+            format!("octopuses flashing all at once within {} steps", MAX_STEPS),
+        )),
     }
-
-    (flash_count, grid)
 }
 
-#[allow(dead_code)]
-fn dbg_grid(grid: &Grid) {
-    for y in 0..10 {
-        for x in 0..10 {
-            print!("{}", grid[y * 10 + x])
+/// returns how many octopuses flashed
+fn step(grid: &mut Grid) -> usize {
+    let mut flashed = vec![false; grid.points.len()];
+    let mut to_visit: Vec<Point> = grid.cells().map(|(p, _)| p).collect();
+
+    while let Some(p) = to_visit.pop() {
+        let idx = grid.idx(p);
+        grid.points[idx] += 1;
+        if grid.points[idx] > 9 && !flashed[idx] {
+            flashed[idx] = true;
+            to_visit.extend(grid.neighbours8(p.x, p.y));
+        }
+    }
+
+    let mut flash_count = 0;
+    for (cell, flashed) in grid.points.iter_mut().zip(flashed) {
+        if flashed {
+            *cell = 0;
+            flash_count += 1;
         }
-        println!();
     }
-    println!();
+    flash_count
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
-    const TEST_INPUT: &str = "5483143223
-2745854711
-5264556173
-6141336146
-6357385478
-4167524645
-2176841721
-6882881134
-4846848554
-5283751526
-";
+    const TEST_INPUT: &str = include_str!("../resources/examples/day11/example.txt");
 
     #[test]
-    fn test_neighbourgs() {
-        let v: Vec<(usize, usize)> = vec![(0, 1), (1, 0), (1, 1)];
-        assert_eq!(v, neighbours(0, 0))
+    fn test_step() {
+        let mut grid = parse(TEST_INPUT).unwrap();
+        assert_eq!(0, step(&mut grid), "step 1");
+        assert_eq!(6, grid.points[0]);
+        assert_eq!(35, step(&mut grid), "step 2");
     }
 
     #[test]
-    fn test_next_grid() {
-        let g0 = parse(TEST_INPUT);
-        let (c1, g1) = next_grid(g0);
-        dbg_grid(&g1);
-        assert_eq!(g1[0], 6);
-        assert_eq!(c1, 0, "step 1");
-        let (c2, _g2) = next_grid(g1);
-        dbg_grid(&_g2);
-        assert_eq!(c2, 35, "step 2");
+    fn test_solve1() {
+        assert_eq!(1656, solve1(&parse(TEST_INPUT).unwrap()));
     }
 
     #[test]
-    fn test_solve1() {
-        assert_eq!(1656, solve1(&parse(TEST_INPUT)));
+    fn test_solve2() {
+        assert_eq!(195, solve2(&parse(TEST_INPUT).unwrap()));
     }
 
     #[test]
-    fn test_solve2() {
-        assert_eq!(195, solve2(&parse(TEST_INPUT)));
+    fn test_never_synchronise() {
+        let first_line = TEST_INPUT.lines().next().unwrap();
+        assert!(parse(first_line).is_err());
     }
 }
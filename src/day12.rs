@@ -1,8 +1,33 @@
 use std::collections::{BTreeMap, BTreeSet};
 
-pub fn solve() -> (usize, usize) {
-    let graph = Graph::from_str(include_str!("../resources/day12.txt"));
-    (solve1(&graph), solve2(&graph))
+use nom::character::complete::{alpha1, char};
+use nom::sequence::separated_pair;
+
+use crate::search::{self, SearchProblem};
+use crate::utils::parse_lines;
+use crate::{ParseError, Solution};
+
+pub struct Day12;
+
+impl Solution for Day12 {
+    const DAY: u8 = 12;
+    const INPUT: &'static str = include_str!("../resources/day12.txt");
+
+    type Input = Graph;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(raw: &str) -> Result<Self::Input, ParseError> {
+        Graph::from_str(raw)
+    }
+
+    fn part1(graph: &Self::Input) -> Self::Answer1 {
+        solve1(graph)
+    }
+
+    fn part2(graph: &Self::Input) -> Self::Answer2 {
+        solve2(graph)
+    }
 }
 
 fn solve1(graph: &Graph) -> usize {
@@ -20,7 +45,7 @@ enum Cave {
 }
 
 #[derive(Debug)]
-struct Graph {
+pub struct Graph {
     n: usize,
     // adjacency matrix, there are merely 20 or so entries
     // so even a sparse graph doesn't consume much mem
@@ -34,19 +59,26 @@ struct Graph {
 }
 
 impl Graph {
-    fn from_str(raw: &str) -> Self {
-        let pairs: Vec<(&str, &str)> = raw
-            .split_terminator('\n')
-            .map(|l| {
-                let pair: [&str; 2] = l.split('-').collect::<Vec<_>>().try_into().unwrap();
-                (pair[0], pair[1])
-            })
-            .collect();
-        let n = raw
-            .split_terminator('\n')
-            .flat_map(|l| l.split('-'))
-            .collect::<BTreeSet<_>>()
-            .len();
+    fn from_str(raw: &str) -> Result<Self, ParseError> {
+        let pairs: Vec<(&str, &str)> = parse_lines(
+            raw,
+            separated_pair(alpha1, char('-'), alpha1),
+            "a line like `start-A`",
+        )?;
+        let names = pairs
+            .iter()
+            .flat_map(|(a, b)| [*a, *b])
+            .collect::<BTreeSet<_>>();
+        for name in ["start", "end"] {
+            if !names.contains(name) {
+                return Err(ParseError::eof(raw, format!("a `{}` cave", name)));
+            }
+        }
+        let n = names.len();
+        // the visited caves are stored in a u64
+        if n > 64 {
+            return Err(ParseError::eof(raw, "at most 64 caves"));
+        }
         let mut connections = vec![false; n * n];
         let mut caves = vec![Cave::Small; n];
 
@@ -86,162 +118,89 @@ impl Graph {
             .into_iter()
             .map(|(k, v)| (k.to_string(), v))
             .collect();
-        Self {
+        Ok(Self {
             n,
             mappings,
             connections,
             caves,
-        }
+        })
     }
 
     fn count_path_to_end(&self, can_return: bool) -> usize {
-        let mut seen = vec![false; self.n];
-        seen[0] = true;
-        let mut paths = RoseTree::new(0);
-        self.dfs(0, &mut seen, can_return, &mut paths);
-        paths.count_paths(&(self.n - 1))
+        search::count_paths(&Paths {
+            graph: self,
+            can_return,
+        })
     }
 
-    fn dfs(
-        &self,
-        from: usize,
-        seen: &mut [bool],
-        can_return: bool,
-        paths: &mut RoseTree<usize>,
-    ) {
-        for i in self.connections[(self.n * from)..(self.n * (from + 1))]
+    fn neighbours(&self, from: usize) -> impl Iterator<Item = usize> + '_ {
+        self.connections[(self.n * from)..(self.n * (from + 1))]
             .iter()
             .enumerate()
             .filter_map(|(i, &has_edge)| if has_edge { Some(i) } else { None })
-        {
-            if seen[i] {
-                continue;
-            }
-
-            if i == self.n - 1 {
-                paths.push(i);
-                continue;
-            }
-
-            let n = paths.push(i);
-            let mut paths = &mut paths.1[n];
-            match self.caves[i] {
-                Cave::Small => {
-                    if can_return {
-                        // don't mark node as seen, and flip the switch
-                        self.dfs(i, seen, false, &mut paths);
-                    }
-                    seen[i] = true;
-                    self.dfs(i, seen, can_return, &mut paths);
-                    seen[i] = false;
-                }
-                Cave::Big => self.dfs(i, seen, can_return, &mut paths),
-            }
-        }
     }
 }
 
-#[derive(Debug)]
-struct RoseTree<T>(T, Vec<RoseTree<T>>);
+/// Paths from `start` to `end`, visiting small caves at most once, or one of
+/// them twice when `can_return` is set.
+struct Paths<'a> {
+    graph: &'a Graph,
+    can_return: bool,
+}
 
-impl<T> RoseTree<T> {
-    fn new(root: T) -> Self {
-        RoseTree(root, vec![])
+/// current cave, small caves already visited, and whether a small cave can
+/// still be visited a second time
+type State = (usize, u64, bool);
+
+impl SearchProblem for Paths<'_> {
+    type State = State;
+    type Cost = usize;
+
+    fn start(&self) -> State {
+        (0, 1, self.can_return)
     }
 
-    fn push(&mut self, node: T) -> usize
-    where
-        T: PartialEq,
-    {
-        match self.1.iter().enumerate().find(|(_idx, t)| t.0 == node) {
-            Some((i, _)) => i,
-            None => {
-                self.1.push(Self::new(node));
-                self.1.len() - 1
+    fn successors(&self, &(from, seen, can_return): &State, mut f: impl FnMut(State, usize)) {
+        for i in self.graph.neighbours(from) {
+            // start is always the first cave, and can never be visited again
+            if i == 0 {
+                continue;
+            }
+            match self.graph.caves[i] {
+                Cave::Big => f((i, seen, can_return), 1),
+                Cave::Small if seen & (1 << i) == 0 => f((i, seen | (1 << i), can_return), 1),
+                Cave::Small if can_return => f((i, seen, false), 1),
+                Cave::Small => (),
             }
         }
     }
 
-    fn count_paths(&self, target: &T) -> usize
-    where
-        T: Eq,
-    {
-        if &self.0 == target {
-            1
-        } else {
-            self.1.iter().map(|n| n.count_paths(target)).sum()
-        }
+    fn is_goal(&self, &(cave, _, _): &State) -> bool {
+        cave == self.graph.n - 1
     }
-
-    // fn dedup(&mut self)
-    //     where T: Eq + std::hash::Hash + Copy
-    // {
-    //     let mut seen = HashMap::new();
-    //     for c in &self.1 {
-    //         let e = seen.entry(&c.0).or_insert_with(Vec::new);
-    //         e.extend(&c.1);
-    //     }
-    //     let children = seen.into_iter().map(|x| x.1).collect();
-    //     *self = RoseTree(self.0, children);
-    // }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
-    const TEST_INPUT1: &str = "start-A
-start-b
-A-c
-A-b
-b-d
-A-end
-b-end
-";
-
-    const TEST_INPUT2: &str = "dc-end
-HN-start
-start-kj
-dc-start
-dc-HN
-LN-dc
-HN-end
-kj-sa
-kj-HN
-kj-dc
-";
-
-    const TEST_INPUT3: &str = "fs-end
-he-DX
-fs-he
-start-DX
-pj-DX
-end-zg
-zg-sl
-zg-pj
-pj-he
-RW-he
-fs-DX
-pj-RW
-zg-RW
-start-pj
-he-WI
-zg-he
-pj-fs
-start-RW
-";
+    const TEST_INPUT1: &str = include_str!("../resources/examples/day12/example1.txt");
+
+    const TEST_INPUT2: &str = include_str!("../resources/examples/day12/example2.txt");
+
+    const TEST_INPUT3: &str = include_str!("../resources/examples/day12/example3.txt");
 
     #[test]
     fn test_solve1() {
-        assert_eq!(10, solve1(&Graph::from_str(TEST_INPUT1)));
-        assert_eq!(19, solve1(&Graph::from_str(TEST_INPUT2)));
-        assert_eq!(226, solve1(&Graph::from_str(TEST_INPUT3)));
+        assert_eq!(10, solve1(&Graph::from_str(TEST_INPUT1).unwrap()));
+        assert_eq!(19, solve1(&Graph::from_str(TEST_INPUT2).unwrap()));
+        assert_eq!(226, solve1(&Graph::from_str(TEST_INPUT3).unwrap()));
     }
 
     #[test]
     fn test_solve2() {
-        assert_eq!(36, solve2(&Graph::from_str(TEST_INPUT1)));
-        assert_eq!(103, solve2(&Graph::from_str(TEST_INPUT2)));
-        assert_eq!(3509, solve2(&Graph::from_str(TEST_INPUT3)));
+        assert_eq!(36, solve2(&Graph::from_str(TEST_INPUT1).unwrap()));
+        assert_eq!(103, solve2(&Graph::from_str(TEST_INPUT2).unwrap()));
+        assert_eq!(3509, solve2(&Graph::from_str(TEST_INPUT3).unwrap()));
     }
 }
@@ -4,12 +4,43 @@ use nom::branch::alt;
 use nom::bytes::complete::tag;
 use nom::character::complete as character;
 use nom::combinator::map;
-use nom::sequence::preceded;
+use nom::sequence::{preceded, terminated};
 use nom::{multi::separated_list1, sequence::separated_pair, IResult};
 
-pub fn solve() -> (usize, String) {
-    let puzzle = parse_puzzle(include_str!("../resources/day13.txt"));
-    (solve1(&puzzle), solve2(&puzzle))
+use crate::geom::Point2;
+use crate::render::{self, Image, Render, Rgb};
+use crate::utils::parse_all;
+use crate::{ParseError, Solution};
+
+pub struct Day13;
+
+impl Solution for Day13 {
+    const DAY: u8 = 13;
+    const INPUT: &'static str = include_str!("../resources/day13.txt");
+
+    type Input = Puzzle;
+    type Answer1 = usize;
+    type Answer2 = String;
+
+    fn parse(raw: &str) -> Result<Self::Input, ParseError> {
+        parse_puzzle(raw)
+    }
+
+    fn render(puzzle: &Self::Input) -> Option<Image> {
+        Some(
+            GridMap::from_puzzle(puzzle)
+                .fold_all(&puzzle.folds)
+                .render(),
+        )
+    }
+
+    fn part1(puzzle: &Self::Input) -> Self::Answer1 {
+        solve1(puzzle)
+    }
+
+    fn part2(puzzle: &Self::Input) -> Self::Answer2 {
+        solve2(puzzle)
+    }
 }
 
 fn solve1(puzzle: &Puzzle) -> usize {
@@ -18,13 +49,12 @@ fn solve1(puzzle: &Puzzle) -> usize {
 }
 
 fn solve2(puzzle: &Puzzle) -> String {
-    let final_grid = GridMap::from_puzzle(puzzle)
-        .fold_all(&puzzle.folds);
+    let final_grid = GridMap::from_puzzle(puzzle).fold_all(&puzzle.folds);
     // the answer is "BCZRCEAB" but I can't be arsed to code an OCR
     format!("\n{}", final_grid)
 }
 
-type Point = (u32, u32);
+type Point = Point2<u32>;
 
 struct GridMap {
     points: BTreeSet<Point>,
@@ -40,11 +70,11 @@ impl GridMap {
 
 impl std::fmt::Display for GridMap {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let width = self.points.iter().map(|p| p.0).max().unwrap();
-        let height = self.points.iter().map(|p| p.1).max().unwrap();
+        let width = self.points.iter().map(|p| p.x).max().unwrap();
+        let height = self.points.iter().map(|p| p.y).max().unwrap();
         for y in 0..=height {
             for x in 0..=width {
-                if self.points.contains(&(x, y)) {
+                if self.points.contains(&Point2::new(x, y)) {
                     f.write_str("#")?;
                 } else {
                     f.write_str(".")?;
@@ -56,6 +86,15 @@ impl std::fmt::Display for GridMap {
     }
 }
 
+impl Render for GridMap {
+    fn render(&self) -> Image {
+        render::from_text(&self.to_string(), |c| match c {
+            '#' => Rgb::WHITE,
+            _ => Rgb::BLACK,
+        })
+    }
+}
+
 trait Aoc {
     fn fold(self, f: &Fold) -> Self;
     fn fold_all(self, f: &[Fold]) -> Self
@@ -72,22 +111,22 @@ impl Aoc for GridMap {
         match f {
             Fold::X(n) => {
                 let mut points = BTreeSet::new();
-                for (x, y) in &self.points {
-                    if x > n {
-                        points.insert((2 * n - x, *y));
+                for &Point2 { x, y } in &self.points {
+                    if x > *n {
+                        points.insert(Point2::new(2 * n - x, y));
                     } else {
-                        points.insert((*x, *y));
+                        points.insert(Point2::new(x, y));
                     }
                 }
                 Self { points }
             }
             Fold::Y(n) => {
                 let mut points = BTreeSet::new();
-                for (x, y) in &self.points {
-                    if y > n {
-                        points.insert((*x, 2 * n - y));
+                for &Point2 { x, y } in &self.points {
+                    if y > *n {
+                        points.insert(Point2::new(x, 2 * n - y));
                     } else {
-                        points.insert((*x, *y));
+                        points.insert(Point2::new(x, y));
                     }
                 }
                 Self { points }
@@ -107,21 +146,30 @@ enum Fold {
 }
 
 #[derive(Debug, Clone)]
-struct Puzzle {
+pub struct Puzzle {
     points: Vec<Point>,
     folds: Vec<Fold>,
 }
 
-fn parse_puzzle(raw: &str) -> Puzzle {
-    let result = separated_pair(parse_points, character::char('\n'), parse_folds)(raw);
-    let (points, folds) = result.unwrap().1;
-    Puzzle { points, folds }
+fn parse_puzzle(raw: &str) -> Result<Puzzle, ParseError> {
+    let (points, folds) = parse_all(
+        raw,
+        terminated(
+            separated_pair(parse_points, character::char('\n'), parse_folds),
+            character::multispace0,
+        ),
+        "a point like `x,y` or a line like `fold along x=n`",
+    )?;
+    Ok(Puzzle { points, folds })
 }
 
-fn parse_points(raw: &str) -> IResult<&str, Vec<(u32, u32)>> {
+fn parse_points(raw: &str) -> IResult<&str, Vec<Point>> {
     separated_list1(
         character::line_ending,
-        separated_pair(character::u32, character::char(','), character::u32),
+        map(
+            separated_pair(character::u32, character::char(','), character::u32),
+            Point2::from,
+        ),
     )(raw)
 }
 
@@ -154,47 +202,26 @@ fn parse_fold(raw: &str) -> IResult<&str, Fold> {
 mod test {
     use super::*;
 
-    const TEST_INPUT: &str = "6,10
-0,14
-9,10
-0,3
-10,4
-4,11
-6,0
-6,12
-4,1
-0,13
-10,12
-3,4
-3,0
-8,4
-1,10
-2,14
-8,10
-9,0
-
-fold along y=7
-fold along x=5
-";
+    const TEST_INPUT: &str = include_str!("../resources/examples/day13/example.txt");
 
     #[test]
     fn test_parse() {
-        let p = parse_puzzle(TEST_INPUT);
+        let p = parse_puzzle(TEST_INPUT).unwrap();
         assert_eq!(p.points.len(), 18);
-        assert_eq!(p.points[0], (6, 10));
+        assert_eq!(p.points[0], Point2::new(6, 10));
         assert_eq!(p.folds.len(), 2);
         assert!(matches!(p.folds[0], Fold::Y(7)));
     }
 
     #[test]
     fn test_solve1() {
-        let p = parse_puzzle(TEST_INPUT);
+        let p = parse_puzzle(TEST_INPUT).unwrap();
         assert_eq!(17, solve1(&p));
     }
 
     #[test]
     fn test_solve2() {
-        let p = parse_puzzle(TEST_INPUT);
-        assert_eq!("16".to_string(), solve2(&p));
+        let p = parse_puzzle(TEST_INPUT).unwrap();
+        assert_eq!("\n#####\n#...#\n#...#\n#...#\n#####\n", solve2(&p));
     }
 }
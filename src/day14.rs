@@ -3,14 +3,35 @@ use std::collections::BTreeMap;
 use itertools::Itertools;
 use nom::bytes::complete::{tag, take};
 use nom::character::complete as character;
-use nom::combinator::{all_consuming, map};
+use nom::combinator::map;
 use nom::multi::separated_list0;
 use nom::sequence::{separated_pair, terminated};
 use nom::IResult;
 
-pub fn solve() -> (usize, usize) {
-    let puzzle = parse_puzzle(include_str!("../resources/day14.txt"));
-    (solve1(&puzzle), solve2(&puzzle))
+use crate::utils::parse_all;
+use crate::{ParseError, Solution};
+
+pub struct Day14;
+
+impl Solution for Day14 {
+    const DAY: u8 = 14;
+    const INPUT: &'static str = include_str!("../resources/day14.txt");
+
+    type Input = Puzzle;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(raw: &str) -> Result<Self::Input, ParseError> {
+        parse_puzzle(raw)
+    }
+
+    fn part1(puzzle: &Self::Input) -> Self::Answer1 {
+        solve1(puzzle)
+    }
+
+    fn part2(puzzle: &Self::Input) -> Self::Answer2 {
+        solve2(puzzle)
+    }
 }
 
 fn solve1(puzzle: &Puzzle) -> usize {
@@ -49,7 +70,7 @@ fn run_steps(puzzle: &Puzzle, n: usize) -> usize {
     let mut count = count_chars(&pairs);
     *(count.get_mut(&puzzle.polymer[0]).unwrap()) += 1;
 
-    match count.into_iter().map(|(_, c)| c).minmax() {
+    match count.into_values().minmax() {
         itertools::MinMaxResult::MinMax(min, max) => max - min,
         _ => unreachable!(),
     }
@@ -69,20 +90,22 @@ struct Rule {
 }
 
 #[derive(Debug)]
-struct Puzzle {
+pub struct Puzzle {
     polymer: Vec<char>,
     rules: Vec<Rule>,
 }
 
-fn parse_puzzle(raw: &str) -> Puzzle {
-    let (polymer, rules) = all_consuming(separated_pair(
-        parse_polymer,
-        character::line_ending,
-        terminated(parse_rules, character::line_ending),
-    ))(raw)
-    .unwrap()
-    .1;
-    Puzzle { polymer, rules }
+fn parse_puzzle(raw: &str) -> Result<Puzzle, ParseError> {
+    let (polymer, rules) = parse_all(
+        raw,
+        separated_pair(
+            parse_polymer,
+            character::line_ending,
+            terminated(parse_rules, character::line_ending),
+        ),
+        "a polymer template followed by rules like `AB -> C`",
+    )?;
+    Ok(Puzzle { polymer, rules })
 }
 
 fn parse_polymer(input: &str) -> IResult<&str, Vec<char>> {
@@ -112,25 +135,7 @@ fn parse_rules(input: &str) -> IResult<&str, Vec<Rule>> {
 mod test {
     use super::*;
 
-    const TEST_INPUT: &str = "NNCB
-
-CH -> B
-HH -> N
-CB -> H
-NH -> C
-HB -> C
-HC -> B
-HN -> C
-NN -> C
-BH -> H
-NC -> B
-NB -> B
-BN -> B
-BB -> N
-BC -> B
-CC -> N
-CN -> C
-";
+    const TEST_INPUT: &str = include_str!("../resources/examples/day14/example.txt");
     #[test]
     fn test_parse() {
         assert_eq!(parse_polymer("NNCB\n").unwrap().1, vec!['N', 'N', 'C', 'B']);
@@ -142,23 +147,22 @@ CN -> C
                 to: 'B'
             }
         );
-        let puzzle = parse_puzzle(TEST_INPUT);
+        let puzzle = parse_puzzle(TEST_INPUT).unwrap();
         assert_eq!(puzzle.polymer, vec!['N', 'N', 'C', 'B']);
         assert_eq!(puzzle.rules.len(), 16);
     }
 
     #[test]
     fn test_solve1() {
-        let puzzle = parse_puzzle(TEST_INPUT);
+        let puzzle = parse_puzzle(TEST_INPUT).unwrap();
         assert_eq!(1588, solve1(&puzzle));
-        let puzzle = parse_puzzle(include_str!("../resources/day14.txt"));
+        let puzzle = parse_puzzle(include_str!("../resources/day14.txt")).unwrap();
         assert_eq!(3048, solve1(&puzzle));
     }
 
-
     #[test]
     fn test_solve2() {
-        let puzzle = parse_puzzle(TEST_INPUT);
+        let puzzle = parse_puzzle(TEST_INPUT).unwrap();
         assert_eq!(2188189693529, solve2(&puzzle));
     }
 }
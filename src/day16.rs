@@ -1,13 +1,33 @@
 use nom::bits::complete::{tag, take};
 use nom::branch::alt;
-use nom::combinator::flat_map;
+use nom::combinator::{flat_map, map_opt};
 use nom::multi::{count, many_till};
 use nom::sequence::preceded;
 use nom::{bits, combinator::map, sequence::tuple, IResult};
 
-pub fn solve() -> (usize, usize) {
-    let packet = Packet::from_str(include_str!("../resources/day16.txt"));
-    (solve1(&packet), solve2(&packet))
+use crate::{ParseError, Solution};
+
+pub struct Day16;
+
+impl Solution for Day16 {
+    const DAY: u8 = 16;
+    const INPUT: &'static str = include_str!("../resources/day16.txt");
+
+    type Input = Packet;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(raw: &str) -> Result<Self::Input, ParseError> {
+        Packet::from_str(raw)
+    }
+
+    fn part1(packet: &Self::Input) -> Self::Answer1 {
+        solve1(packet)
+    }
+
+    fn part2(packet: &Self::Input) -> Self::Answer2 {
+        solve2(packet)
+    }
 }
 
 fn solve1(pkt: &Packet) -> usize {
@@ -36,15 +56,34 @@ enum PacketType {
 }
 
 #[derive(Debug, PartialEq, Eq)]
-struct Packet {
+pub struct Packet {
     version: u8,
     content: PacketType,
 }
 
 impl Packet {
-    fn from_str(raw: &str) -> Self {
-        let bytes = hex::decode(raw.trim_end()).unwrap();
-        parse_packet_nom(&bytes).unwrap().1
+    fn from_str(raw: &str) -> Result<Self, ParseError> {
+        let hex = raw.trim_end();
+        if let Some(i) = hex.find(|c: char| !c.is_ascii_hexdigit()) {
+            return Err(ParseError::at(raw, &hex[i..], "a hexadecimal digit"));
+        }
+        if !hex.len().is_multiple_of(2) {
+            return Err(ParseError::at(
+                raw,
+                &hex[hex.len()..],
+                "an even number of digits",
+            ));
+        }
+        let bytes = hex::decode(hex).unwrap();
+        match parse_packet_nom(&bytes) {
+            Ok((_, packet)) => Ok(packet),
+            Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => {
+                // two hex digits per byte
+                let offset = (bytes.len() - e.input.len()) * 2;
+                Err(ParseError::at(raw, &hex[offset..], "a valid packet"))
+            }
+            Err(nom::Err::Incomplete(_)) => Err(ParseError::eof(raw, "a valid packet")),
+        }
     }
 
     fn count_versions(&self) -> usize {
@@ -134,8 +173,10 @@ fn parse_bits(raw: (&[u8], usize)) -> IResult<(&[u8], usize), Packet> {
     )(raw)
 }
 
+type Bits<'a> = (&'a [u8], usize);
+
 // parse some number of packets, consuming `l` bits
-fn parse_packet_len(l: usize) -> impl Fn((&[u8], usize)) -> IResult<(&[u8], usize), Vec<Packet>> {
+fn parse_packet_len(l: usize) -> impl Fn(Bits) -> IResult<Bits, Vec<Packet>> {
     move |sub_bits| {
         // unfortunately, nom::combinator::consumed doesn't work
         // for bits, so need to handrol the length counting
@@ -153,15 +194,16 @@ fn parse_packet_len(l: usize) -> impl Fn((&[u8], usize)) -> IResult<(&[u8], usiz
 
 fn parse_optype(raw: (&[u8], usize)) -> IResult<(&[u8], usize), OpType> {
     use OpType::*;
-    map(take(3usize), |c: u8| match c {
-        0 => Sum,
-        1 => Product,
-        2 => Min,
-        3 => Max,
-        5 => Gt,
-        6 => Lt,
-        7 => Eq,
-        _ => unreachable!("{}", c),
+    map_opt(take(3usize), |c: u8| match c {
+        0 => Some(Sum),
+        1 => Some(Product),
+        2 => Some(Min),
+        3 => Some(Max),
+        5 => Some(Gt),
+        6 => Some(Lt),
+        7 => Some(Eq),
+        // 4 is a literal, but it was rejected by `parse_literal`
+        _ => None,
     })(raw)
 }
 
@@ -180,7 +222,7 @@ mod test {
             version: 6,
             content: PacketType::Literal(2021),
         };
-        assert_eq!(Packet::from_str("D2FE28"), expected);
+        assert_eq!(Packet::from_str("D2FE28").unwrap(), expected);
     }
 
     #[test]
@@ -201,7 +243,7 @@ mod test {
                 ],
             ),
         };
-        assert_eq!(Packet::from_str("38006F45291200"), expected);
+        assert_eq!(Packet::from_str("38006F45291200").unwrap(), expected);
     }
 
     #[test]
@@ -226,32 +268,45 @@ mod test {
                 ],
             ),
         };
-        assert_eq!(Packet::from_str("EE00D40C823060"), expected);
+        assert_eq!(Packet::from_str("EE00D40C823060").unwrap(), expected);
     }
 
     #[test]
     fn test_solve1() {
-        assert_eq!(16, solve1(&Packet::from_str("8A004A801A8002F478")));
-        assert_eq!(12, solve1(&Packet::from_str("620080001611562C8802118E34")));
+        assert_eq!(16, solve1(&Packet::from_str("8A004A801A8002F478").unwrap()));
+        assert_eq!(
+            12,
+            solve1(&Packet::from_str("620080001611562C8802118E34").unwrap())
+        );
         assert_eq!(
             23,
-            solve1(&Packet::from_str("C0015000016115A2E0802F182340"))
+            solve1(&Packet::from_str("C0015000016115A2E0802F182340").unwrap())
         );
         assert_eq!(
             31,
-            solve1(&Packet::from_str("A0016C880162017C3686B18A3D4780"))
+            solve1(&Packet::from_str("A0016C880162017C3686B18A3D4780").unwrap())
         );
     }
 
     #[test]
     fn test_solve2() {
-        assert_eq!(3, solve2(&Packet::from_str("C200B40A82")));
-        assert_eq!(54, solve2(&Packet::from_str("04005AC33890")));
-        assert_eq!(7, solve2(&Packet::from_str("880086C3E88112")));
-        assert_eq!(9, solve2(&Packet::from_str("CE00C43D881120")));
-        assert_eq!(1, solve2(&Packet::from_str("D8005AC2A8F0")));
-        assert_eq!(0, solve2(&Packet::from_str("F600BC2D8F")));
-        assert_eq!(0, solve2(&Packet::from_str("9C005AC2F8F0")));
-        assert_eq!(1, solve2(&Packet::from_str("9C0141080250320F1802104A08")));
+        assert_eq!(3, solve2(&Packet::from_str("C200B40A82").unwrap()));
+        assert_eq!(54, solve2(&Packet::from_str("04005AC33890").unwrap()));
+        assert_eq!(7, solve2(&Packet::from_str("880086C3E88112").unwrap()));
+        assert_eq!(9, solve2(&Packet::from_str("CE00C43D881120").unwrap()));
+        assert_eq!(1, solve2(&Packet::from_str("D8005AC2A8F0").unwrap()));
+        assert_eq!(0, solve2(&Packet::from_str("F600BC2D8F").unwrap()));
+        assert_eq!(0, solve2(&Packet::from_str("9C005AC2F8F0").unwrap()));
+        assert_eq!(
+            1,
+            solve2(&Packet::from_str("9C0141080250320F1802104A08").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let err = Packet::from_str("D2FG28\n").unwrap_err();
+        assert_eq!((1, 4), (err.line, err.column));
+        assert!(Packet::from_str("D2F").is_err());
     }
 }
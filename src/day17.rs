@@ -1,18 +1,36 @@
+use crate::utils::{parse_all, parse_i32};
+use crate::{ParseError, Solution};
 use itertools::Itertools;
 use nom::{
     bytes::complete::tag,
     sequence::{preceded, tuple},
 };
-use crate::utils::parse_i32;
 
-pub fn solve() -> (i32, usize) {
-    let ta = TargetArea::from_str(include_str!("../resources/day17.txt"));
-    (solve1(&ta), solve2(&ta))
+pub struct Day17;
+
+impl Solution for Day17 {
+    const DAY: u8 = 17;
+    const INPUT: &'static str = include_str!("../resources/day17.txt");
+
+    type Input = TargetArea;
+    type Answer1 = i32;
+    type Answer2 = usize;
+
+    fn parse(raw: &str) -> Result<Self::Input, ParseError> {
+        TargetArea::from_str(raw)
+    }
+
+    fn part1(ta: &Self::Input) -> Self::Answer1 {
+        solve1(ta)
+    }
+
+    fn part2(ta: &Self::Input) -> Self::Answer2 {
+        solve2(ta)
+    }
 }
 
 fn solve1(ta: &TargetArea) -> i32 {
     (-ta.y0..-ta.y1)
-        .into_iter()
         .map(|vy0| y_trajectory(vy0, ta.y0, ta.y1))
         .take_while(|x| x.is_some())
         .map(|x| x.unwrap())
@@ -23,7 +41,6 @@ fn solve1(ta: &TargetArea) -> i32 {
 fn solve2(ta: &TargetArea) -> usize {
     (0..=ta.x1)
         .cartesian_product(ta.y1..=-ta.y1)
-        .into_iter()
         .filter(|(vx0, vy0)| trajectory(*vx0, *vy0, ta).is_some())
         .count()
 }
@@ -87,7 +104,7 @@ fn y_trajectory(vy0: i32, ty0: i32, ty1: i32) -> Option<i32> {
 // }
 
 #[derive(Debug)]
-struct TargetArea {
+pub struct TargetArea {
     x0: i32,
     x1: i32,
     y0: i32,
@@ -95,26 +112,31 @@ struct TargetArea {
 }
 
 impl TargetArea {
-    fn from_str(raw: &str) -> Self {
-        let (x0, x1, y0, y1) = preceded(
-            tag("target area: "),
-            tuple((
-                preceded(tag("x="), parse_i32),
-                preceded(tag(".."), parse_i32),
-                preceded(tag(", y="), parse_i32),
-                preceded(tag(".."), parse_i32),
-            )),
-        )(raw)
-        .unwrap()
-        .1;
-
-        assert!(y0 < 0);
-        assert!(y1 < 0);
+    fn from_str(raw: &str) -> Result<Self, ParseError> {
+        let (x0, x1, y0, y1) = parse_all(
+            raw.trim_end(),
+            preceded(
+                tag("target area: "),
+                tuple((
+                    preceded(tag("x="), parse_i32),
+                    preceded(tag(".."), parse_i32),
+                    preceded(tag(", y="), parse_i32),
+                    preceded(tag(".."), parse_i32),
+                )),
+            ),
+            "a line like `target area: x=20..30, y=-10..-5`",
+        )?;
+
+        // both parts assume the target is below the launcher
+        if y0 >= 0 || y1 >= 0 {
+            let y = raw.find("y=").unwrap() + 2;
+            return Err(ParseError::at(raw, &raw[y..], "a range below 0"));
+        }
 
         // ensure x0 and y0 are the closest to 0 compared to x1 and y1
         let (x0, x1) = if x0 >= x1 { (x1, x0) } else { (x0, x1) };
         let (y0, y1) = if y0 >= y1 { (y0, y1) } else { (y1, y0) };
-        Self { x0, x1, y0, y1 }
+        Ok(Self { x0, x1, y0, y1 })
     }
 }
 
@@ -122,17 +144,17 @@ impl TargetArea {
 mod test {
     use super::*;
 
-    const TEST_INPUT: &str = "target area: x=20..30, y=-10..-5";
+    const TEST_INPUT: &str = include_str!("../resources/examples/day17/example.txt");
 
     #[test]
     fn test_solve1() {
-        let ta = TargetArea::from_str(TEST_INPUT);
+        let ta = TargetArea::from_str(TEST_INPUT).unwrap();
         assert_eq!(45, solve1(&ta));
     }
 
     #[test]
     fn test_solve2() {
-        let ta = TargetArea::from_str(TEST_INPUT);
+        let ta = TargetArea::from_str(TEST_INPUT).unwrap();
         assert_eq!(112, solve2(&ta));
     }
 }
@@ -1,12 +1,39 @@
 use std::fmt::Display;
 
-pub fn solve() -> (usize, usize) {
-    let trees = parse_input(include_str!("../resources/day18.txt"));
-    (solve1(&trees), solve2(trees))
+use crate::{ParseError, Solution};
+
+pub struct Day18;
+
+impl Solution for Day18 {
+    const DAY: u8 = 18;
+    const INPUT: &'static str = include_str!("../resources/day18.txt");
+
+    type Input = Vec<Tree>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(raw: &str) -> Result<Self::Input, ParseError> {
+        parse_input(raw)
+    }
+
+    fn part1(trees: &Self::Input) -> Self::Answer1 {
+        solve1(trees)
+    }
+
+    fn part2(trees: &Self::Input) -> Self::Answer2 {
+        solve2(trees.clone())
+    }
 }
 
-fn parse_input(raw: &str) -> Vec<Tree> {
-    raw.lines().map(Tree::from_str).collect()
+fn parse_input(raw: &str) -> Result<Vec<Tree>, ParseError> {
+    let trees = raw
+        .lines()
+        .map(|line| Tree::parse(raw, line))
+        .collect::<Result<Vec<_>, _>>()?;
+    if trees.is_empty() {
+        return Err(ParseError::eof(raw, "a snailfish number"));
+    }
+    Ok(trees)
 }
 
 fn solve1(trees: &[Tree]) -> usize {
@@ -20,8 +47,9 @@ fn solve2(mut trees: Vec<Tree>) -> usize {
         use std::cmp::Reverse;
         Reverse(a.magnitude(0)).cmp(&Reverse(b.magnitude(0)))
     });
+    // snailfish addition isn't commutative, so both orders must be checked
     for x in 0..n {
-        for y in x + 1..n {
+        for y in (0..n).filter(|y| *y != x) {
             let m = trees[x].magnitude(0) * 3 + 2 * trees[y].magnitude(0);
             // reducing a tree always lower its magnitude
             if m > max_so_far {
@@ -42,7 +70,7 @@ fn sum_trees(trees: &[Tree]) -> Tree {
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
-struct Tree {
+pub struct Tree {
     nodes: Vec<Option<Node>>,
 }
 
@@ -60,29 +88,58 @@ enum Direction {
 
 impl Tree {
     fn new() -> Self {
-        let nodes: Vec<Option<Node>> = std::iter::repeat(None).take(1 << 7).collect();
+        let nodes: Vec<Option<Node>> = vec![None; 1 << 7];
         Self { nodes }
     }
 
+    #[cfg(test)]
     fn from_str(raw: &str) -> Self {
+        Self::parse(raw, raw).unwrap()
+    }
+
+    /// `line` must be a slice of `input`, which is only used to locate errors
+    fn parse(input: &str, line: &str) -> Result<Self, ParseError> {
         use Node::*;
         let mut nodes = Self::new().nodes;
         let mut idx = 0;
-        for c in raw.chars() {
-            match c {
-                '[' => {
+        // true when a pair or a number is expected
+        let mut open = true;
+        for (i, c) in line.char_indices() {
+            let err = |expected| Err(ParseError::at(input, &line[i..], expected));
+            match (open, c) {
+                (true, '[') => {
+                    if idx * 2 + 2 >= nodes.len() {
+                        return err("a less nested pair");
+                    }
                     nodes[idx] = Some(Pair);
                     idx = idx * 2 + 1;
                 }
-                ']' => idx = (idx - 1) / 2,
-                ',' => idx += 1,
-                d if d.is_digit(10) => {
+                (true, d) if d.is_ascii_digit() && idx != 0 => {
                     nodes[idx] = Some(Num(d.to_digit(10).unwrap() as u8));
+                    open = false;
                 }
-                _ => unreachable!("unknown char: {}", c),
+                (true, _) if idx == 0 => return err("`[`"),
+                (true, _) => return err("`[` or a digit"),
+                (false, ',') if idx % 2 == 1 => {
+                    idx += 1;
+                    open = true;
+                }
+                (false, ']') if idx != 0 && idx % 2 == 0 => idx = (idx - 1) / 2,
+                (false, _) if idx == 0 => return err("the end of the line"),
+                (false, _) if idx % 2 == 1 => return err("`,`"),
+                (false, _) => return err("`]`"),
             }
         }
-        Self { nodes }
+        if open || idx != 0 {
+            let expected = match (open, idx % 2) {
+                (true, _) if idx == 0 => "`[`",
+                (true, _) => "`[` or a digit",
+                (false, 1) => "`,`",
+                (false, _) => "`]`",
+            };
+            return Err(ParseError::at(input, &line[line.len()..], expected));
+        }
+        Ok(Self { nodes })
     }
 
     fn fmt_idx(&self, f: &mut std::fmt::Formatter<'_>, idx: usize) -> std::fmt::Result {
@@ -317,7 +374,7 @@ mod test {
 
     #[test]
     fn test_add() {
-        let t1 = Tree::from_str("[[1,2],[3,4]");
+        let t1 = Tree::from_str("[[1,2],[3,4]]");
         let t2 = Tree::from_str("[1,2]") + Tree::from_str("[3,4]");
         assert_eq!(t1, t2);
 
@@ -365,7 +422,7 @@ mod test {
 [5,5]
 [6,6]
 ";
-        let actual = sum_trees(&parse_input(test_input));
+        let actual = sum_trees(&parse_input(test_input).unwrap());
         assert_eq!(expected, actual);
     }
 
@@ -383,7 +440,7 @@ mod test {
 [[[[4,2],2],6],[8,7]]";
         assert_eq!(
             Tree::from_str("[[[[8,7],[7,7]],[[8,6],[7,7]]],[[[0,7],[6,6]],[8,7]]]"),
-            sum_trees(&parse_input(test_input))
+            sum_trees(&parse_input(test_input).unwrap())
         );
     }
 
@@ -396,25 +453,15 @@ mod test {
         );
     }
 
-    const TEST_INPUT: &str = "[[[0,[5,8]],[[1,7],[9,6]]],[[4,[1,2]],[[1,4],2]]]
-[[[5,[2,8]],4],[5,[[9,9],0]]]
-[6,[[[6,2],[5,6]],[[7,6],[4,7]]]]
-[[[6,[0,7]],[0,9]],[4,[9,[9,0]]]]
-[[[7,[6,4]],[3,[1,3]]],[[[5,5],1],9]]
-[[6,[[7,3],[3,2]]],[[[3,8],[5,7]],4]]
-[[[[5,4],[7,7]],8],[[8,3],8]]
-[[9,3],[[9,9],[6,[4,9]]]]
-[[2,[[7,7],7]],[[5,8],[[9,3],[0,2]]]]
-[[[[5,2],5],[8,[3,7]]],[[5,[7,5]],[4,4]]]
-";
+    const TEST_INPUT: &str = include_str!("../resources/examples/day18/example.txt");
 
     #[test]
     fn test_solve1() {
-        assert_eq!(4140, solve1(&parse_input(TEST_INPUT)));
+        assert_eq!(4140, solve1(&parse_input(TEST_INPUT).unwrap()));
     }
 
     #[test]
     fn test_solve2() {
-        assert_eq!(3993, solve2(parse_input(TEST_INPUT)));
+        assert_eq!(3993, solve2(parse_input(TEST_INPUT).unwrap()));
     }
 }
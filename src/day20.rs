@@ -1,71 +1,117 @@
 use itertools::Itertools;
+use nom::bytes::complete::is_a;
+use nom::character::complete::{line_ending, multispace0};
+use nom::multi::{count, separated_list1};
+use nom::sequence::{separated_pair, terminated};
 
-pub fn solve() -> (usize, usize) {
-    let p = Puzzle::from_str(include_str!("../resources/day20.txt"));
-    (solve1(p.clone()), solve2(p))
+use crate::render::{Image, Render};
+use crate::trace;
+use crate::utils::{check_grid, parse_all, Grid};
+use crate::{ParseError, Solution};
+
+pub struct Day20;
+
+impl Solution for Day20 {
+    const DAY: u8 = 20;
+    const INPUT: &'static str = include_str!("../resources/day20.txt");
+
+    type Input = Puzzle;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(raw: &str) -> Result<Self::Input, ParseError> {
+        Puzzle::from_str(raw)
+    }
+
+    fn check(raw: &str) -> Vec<ParseError> {
+        Puzzle::check(raw)
+    }
+
+    /// the image after the 50 enhancements of part 2
+    fn render(puzzle: &Self::Input) -> Option<Image> {
+        Some(Pixels(&puzzle.clone().enhance_n(50).image).render())
+    }
+
+    fn part1(puzzle: &Self::Input) -> Self::Answer1 {
+        solve1(puzzle.clone())
+    }
+
+    fn part2(puzzle: &Self::Input) -> Self::Answer2 {
+        solve2(puzzle.clone())
+    }
 }
 
 fn solve1(puzzle: Puzzle) -> usize {
-    let p = puzzle.enhance_n(2);
-    p.image.into_iter().filter(|b| *b).count()
+    puzzle.enhance_n(2).lit_count()
 }
 
 fn solve2(puzzle: Puzzle) -> usize {
-    puzzle
-        .enhance_n(50)
-        .image
-        .into_iter()
-        .filter(|b| *b)
-        .count()
+    puzzle.enhance_n(50).lit_count()
 }
 
 #[derive(Debug, Clone)]
-struct Puzzle {
+pub struct Puzzle {
     alg: Vec<bool>,
-    side: usize,
-    image: Vec<bool>,
+    image: Grid<bool>,
+}
+
+fn is_lit(c: char) -> Option<bool> {
+    match c {
+        '#' => Some(true),
+        '.' => Some(false),
+        _ => None,
+    }
 }
 
 impl Puzzle {
-    fn from_str(raw: &str) -> Self {
-        let mut ls = raw.lines();
-        let alg = ls
-            .next()
-            .unwrap()
-            .chars()
-            .map(|c| match c {
-                '#' => true,
-                '.' => false,
-                _ => unreachable!("unknown char: {}", c),
-            })
-            .collect();
-
-        // skip empty line
-        ls.next();
-        let mut side = 0;
-
-        let image = ls
-            .enumerate()
-            .flat_map(|(y, xs)| {
-                side = y + 1;
-                xs.chars().map(move |c| match c {
-                    '#' => true,
-                    '.' => false,
-                    _ => unreachable!("unknown char: {}", c),
-                })
-            })
-            .collect();
-
-        Puzzle { alg, image, side }
+    fn from_str(raw: &str) -> Result<Self, ParseError> {
+        let (alg, rows) = parse_all(
+            raw,
+            terminated(
+                separated_pair(
+                    is_a("#."),
+                    count(line_ending, 2),
+                    separated_list1(line_ending, is_a("#.")),
+                ),
+                multispace0,
+            ),
+            "`#` or `.`",
+        )?;
+        if alg.len() != 512 {
+            let rest = &alg[alg.len().min(512)..];
+            return Err(ParseError::at(raw, rest, "512 pixels in the algorithm"));
+        }
+
+        Ok(Puzzle {
+            alg: alg.chars().map(|c| c == '#').collect(),
+            image: Grid::from_lines(raw, rows, is_lit, "`#` or `.`")?,
+        })
+    }
+
+    fn check(raw: &str) -> Vec<ParseError> {
+        let is_pixel = |c| c == '#' || c == '.';
+        let mut lines = raw.lines();
+        let alg = lines.next().unwrap_or(&raw[..0]);
+        let mut errors = check_grid(raw, [alg], Some((512, 1)), is_pixel, "`#` or `.`");
+        match lines.next() {
+            Some("") => errors.extend(check_grid(raw, lines, None, is_pixel, "`#` or `.`")),
+            Some(line) => errors.push(ParseError::at(raw, line, "an empty line")),
+            None => errors.push(ParseError::eof(raw, "an empty line, then the image")),
+        }
+        errors
+    }
+
+    fn lit_count(&self) -> usize {
+        self.image.points.iter().filter(|b| **b).count()
     }
 
     fn enhance_n(self, n: usize) -> Self {
         let mut p = self;
 
+        trace::frame(0, || Pixels(&p.image));
         for i in 0..n {
-            // println!("\n--------- enhancing iteration {}\n", i);
-            let capa = (p.side + 2) * (p.side + 2);
-            let mut image = Vec::with_capacity(capa);
+            // the infinite rest of the image blinks when the algorithm lights
+            // up dark areas
             let default = if p.alg[0] {
                 if i % 2 == 0 {
                     p.alg[p.alg.len() - 1]
@@ -76,14 +122,11 @@ impl Puzzle {
                 false
             };
 
-            for y in 0..((p.side + 2) as isize) {
-                for x in 0..((p.side + 2) as isize) {
-                    image.push(p.is_next_pixel_lit(x - 1, y - 1, default))
-                }
-            }
-
-            p.side += 2;
+            let image = Grid::from_fn(p.image.width + 2, p.image.height + 2, |x, y| {
+                p.is_next_pixel_lit(x as isize - 1, y as isize - 1, default)
+            });
             p.image = image;
+            trace::frame(i + 1, || Pixels(&p.image));
         }
         p
     }
@@ -93,59 +136,47 @@ impl Puzzle {
             .into_iter()
             .cartesian_product([x - 1, x, x + 1])
             .fold(0, |acc, (y, x)| {
-                let out_of_bound =
-                    x < 0 || y < 0 || x >= self.side as isize || y >= self.side as isize;
-                // println!(
-                //     "checking ({},{}) oob? {}, side: {}",
-                //     x, y, out_of_bound, self.side
-                // );
-                let x = if out_of_bound {
-                    if default {
-                        1
-                    } else {
-                        0
-                    }
+                let lit = if x < 0 || y < 0 {
+                    default
                 } else {
-                    let idx = (y as usize) * self.side + (x as usize);
-                    if self.image[idx] {
-                        1
-                    } else {
-                        0
-                    }
+                    self.image
+                        .get(x as usize, y as usize)
+                        .copied()
+                        .unwrap_or(default)
                 };
-
-                (acc << 1) + x
+                (acc << 1) + lit as usize
             });
         self.alg[idx]
     }
 }
 
+fn pixel(lit: &bool) -> char {
+    if *lit {
+        '#'
+    } else {
+        '.'
+    }
+}
+
 impl std::fmt::Display for Puzzle {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        self.alg.iter().try_for_each(|b| {
-            if *b {
-                f.write_str("#")?;
-            } else {
-                f.write_str(".")?;
-            };
-            std::fmt::Result::Ok(())
-        })?;
+        let alg = self.alg.iter().map(pixel).collect::<String>();
+        write!(f, "{}\n\n{}", alg, Pixels(&self.image))
+    }
+}
 
-        f.write_str("\n")?;
-        f.write_str("\n")?;
+/// the image alone, as `#` and `.`
+struct Pixels<'a>(&'a Grid<bool>);
 
-        for y in 0..self.side {
-            for x in 0..self.side {
-                let lit = self.image[y * self.side + x];
-                if lit {
-                    f.write_str("#")?;
-                } else {
-                    f.write_str(".")?;
-                }
-            }
-            f.write_str("\n")?;
-        }
-        Ok(())
+impl std::fmt::Display for Pixels<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.map(pixel))
+    }
+}
+
+impl Render for Pixels<'_> {
+    fn render(&self) -> Image {
+        self.0.render()
     }
 }
 
@@ -153,21 +184,15 @@ impl std::fmt::Display for Puzzle {
 mod test {
     use super::*;
 
-    const TEST_INPUT: &str = "..#.#..#####.#.#.#.###.##.....###.##.#..###.####..#####..#....#..#..##..###..######.###...####..#..#####..##..#.#####...##.#.#..#.##..#.#......#.###.######.###.####...#.##.##..#..#..#####.....#.#....###..#.##......#.....#..#..#..##..#...##.######.####.####.#.#...#.......#..#.#.#...####.##.#......#..#...##.#.##..#...##.#.##..###.#......#.#.......#.#.#.####.###.##...#.....####.#..#..#.##.#....##..#.####....##...##..#...#......#.#.......#.......##..####..#...#.#.#...##..#.#..###..#####........#..####......#..#
-
-#..#.
-#....
-##..#
-..#..
-..###";
+    const TEST_INPUT: &str = include_str!("../resources/examples/day20/example.txt");
 
     #[test]
     fn test_solve1() {
-        assert_eq!(35, solve1(Puzzle::from_str(TEST_INPUT)));
+        assert_eq!(35, solve1(Puzzle::from_str(TEST_INPUT).unwrap()));
     }
 
     #[test]
     fn test_solve2() {
-        assert_eq!(3351, solve2(Puzzle::from_str(TEST_INPUT)));
+        assert_eq!(3351, solve2(Puzzle::from_str(TEST_INPUT).unwrap()));
     }
 }
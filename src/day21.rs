@@ -1,14 +1,35 @@
-use std::collections::hash_map::Entry::{Occupied, Vacant};
 use std::collections::HashMap;
 
 use nom::character::complete as character;
+use nom::combinator::verify;
 use nom::sequence::tuple;
 use nom::IResult;
 use nom::{bytes::complete::tag, sequence::preceded};
 
-pub fn solve() -> (usize, usize) {
-    let puzzle = parse_puzzle(include_str!("../resources/day21.txt"));
-    (solve1(puzzle), solve2(puzzle))
+use crate::utils::parse_lines;
+use crate::{ParseError, Solution};
+
+pub struct Day21;
+
+impl Solution for Day21 {
+    const DAY: u8 = 21;
+    const INPUT: &'static str = include_str!("../resources/day21.txt");
+
+    type Input = (usize, usize);
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(raw: &str) -> Result<Self::Input, ParseError> {
+        parse_puzzle(raw)
+    }
+
+    fn part1(puzzle: &Self::Input) -> Self::Answer1 {
+        solve1(*puzzle)
+    }
+
+    fn part2(puzzle: &Self::Input) -> Self::Answer2 {
+        solve2(*puzzle)
+    }
 }
 
 fn solve1(puzzle: (usize, usize)) -> usize {
@@ -57,50 +78,43 @@ fn solve2(puzzle: (usize, usize)) -> usize {
         ],
     };
 
-    let mut states = vec![initial_state];
-    let mut victories = vec![0; 2];
-
-    let mut states_count = HashMap::from([(initial_state, 1)]);
-
-    while let Some(st) = states.pop() {
-        let idx = match st.current {
-            Player::One => 0,
-            Player::Two => 1,
-        };
-        let ps = &st.states[idx];
-        let occurrences = *states_count.get(&st).unwrap();
-
-        if st.states[0].score >= 21 {
-            victories[0] += occurrences;
-            continue;
-        } else if st.states[1].score >= 21 {
-            victories[1] += occurrences;
-            continue;
-        }
-
-        for (i, count) in &PROBAS {
-            let mut pos = ps.pos + i;
-            if pos > 10 {
-                pos -= 10;
-            };
-            let score = ps.score + pos;
-            let new_state = PlayerState { pos, score };
+    // every turn increases the sum of the scores, so expanding the states by
+    // increasing sum guarantees that all the universes leading to a state
+    // have been counted before it is expanded.
+    // Non final states have scores of at most 20, so the sum can't exceed 50.
+    let mut by_total: Vec<HashMap<GameState, usize>> = vec![HashMap::new(); 51];
+    by_total[0].insert(initial_state, 1);
+    let mut victories = [0; 2];
+
+    for total in 0..by_total.len() {
+        for (st, occurrences) in std::mem::take(&mut by_total[total]) {
+            if st.states[0].score >= 21 {
+                victories[0] += occurrences;
+                continue;
+            } else if st.states[1].score >= 21 {
+                victories[1] += occurrences;
+                continue;
+            }
 
-            let mut new_st = GameState {
-                current: !st.current,
-                states: st.states,
+            let idx = match st.current {
+                Player::One => 0,
+                Player::Two => 1,
             };
-            new_st.states[idx] = new_state;
-            let entry = states_count.entry(new_st);
-            match entry {
-                Vacant(_) => {
-                    states_count.insert(new_st, occurrences * count);
-                    states.push(new_st);
-                }
-                Occupied(mut e) => {
-                    let e = e.get_mut();
-                    *e += occurrences * count;
-                }
+            let ps = &st.states[idx];
+            for (i, count) in &PROBAS {
+                let mut pos = ps.pos + i;
+                if pos > 10 {
+                    pos -= 10;
+                };
+                let score = ps.score + pos;
+                let new_state = PlayerState { pos, score };
+
+                let mut new_st = GameState {
+                    current: !st.current,
+                    states: st.states,
+                };
+                new_st.states[idx] = new_state;
+                *by_total[total + pos as usize].entry(new_st).or_insert(0) += occurrences * count;
             }
         }
     }
@@ -141,42 +155,43 @@ impl std::ops::Not for Player {
     }
 }
 
-fn parse_puzzle(raw: &str) -> (usize, usize) {
-    let mut ls = raw.lines();
-    let p1 = parse_line(ls.next().unwrap());
-    let p2 = parse_line(ls.next().unwrap());
-    (p1 as _, p2 as _)
+fn parse_puzzle(raw: &str) -> Result<(usize, usize), ParseError> {
+    let positions = parse_lines(
+        raw,
+        parse_line,
+        "a line like `Player 1 starting position: 4`",
+    )?;
+    match positions[..] {
+        [p1, p2] => Ok((p1 as _, p2 as _)),
+        _ => Err(ParseError::eof(raw, "exactly two players")),
+    }
 }
 
-fn parse_line(raw: &str) -> u8 {
-    let r: IResult<&str, u8> = preceded(
+fn parse_line(raw: &str) -> IResult<&str, u8> {
+    preceded(
         tuple((tag("Player "), character::u8, tag(" starting position: "))),
-        character::u8,
-    )(raw);
-    r.unwrap().1
+        verify(character::u8, |p| (1..=10).contains(p)),
+    )(raw)
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
-    const TEST_INPUT: &str = "Player 1 starting position: 4
-Player 2 starting position: 8
-";
+    const TEST_INPUT: &str = include_str!("../resources/examples/day21/example.txt");
 
     #[test]
     fn test_parse() {
-        assert_eq!((4, 8), parse_puzzle(TEST_INPUT))
+        assert_eq!((4, 8), parse_puzzle(TEST_INPUT).unwrap())
     }
 
     #[test]
     fn test_solve1() {
-        assert_eq!(739785, solve1(parse_puzzle(TEST_INPUT)));
+        assert_eq!(739785, solve1(parse_puzzle(TEST_INPUT).unwrap()));
     }
 
     #[test]
-    #[ignore] // very slow in debug mode /o\
     fn test_solve2() {
-        assert_eq!(444356092776315, solve2(parse_puzzle(TEST_INPUT)));
+        assert_eq!(444356092776315, solve2(parse_puzzle(TEST_INPUT).unwrap()));
     }
 }
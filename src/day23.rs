@@ -1,28 +1,50 @@
-use ahash::AHashMap;
 use std::fmt::{Display, Formatter};
 use std::iter::Once;
 
-type Map<K, V> = AHashMap<K, V>;
+use crate::render::{self, Image, Render, Rgb};
+use crate::search::{self, SearchProblem};
+use crate::trace;
+use crate::{ParseError, Solution};
 
-pub fn solve() -> (usize, usize) {
-    let grid = parse_input(include_str!("../resources/day23.txt"));
-    (solve1(&grid), solve2(&grid))
+pub struct Day23;
+
+impl Solution for Day23 {
+    const DAY: u8 = 23;
+    const INPUT: &'static str = include_str!("../resources/day23.txt");
+
+    type Input = [[Pod; 4]; 2];
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(raw: &str) -> Result<Self::Input, ParseError> {
+        parse_input(raw)
+    }
+
+    fn render(pods: &Self::Input) -> Option<Image> {
+        Some(Cavern::from_input(pods).render())
+    }
+
+    fn part1(pods: &Self::Input) -> Self::Answer1 {
+        solve1(pods)
+    }
+
+    fn part2(pods: &Self::Input) -> Self::Answer2 {
+        solve2(pods)
+    }
 }
 
 fn solve1(pods: &[[Pod; 4]; 2]) -> usize {
-    let cavern = Cavern::from_input(pods);
-    move_all_pods_rec_cavern(cavern, &mut Map::default(), 0, usize::MAX)
+    min_energy(Cavern::from_input(pods))
 }
 
 fn solve2(pods: &[[Pod; 4]; 2]) -> usize {
     use Pod::*;
     let pods = [pods[0], [D, C, B, A], [D, B, A, C], pods[1]];
-    let cavern = Cavern::from_input(&pods);
-    move_all_pods_rec_cavern(cavern, &mut Map::default(), 0, usize::MAX)
+    min_energy(Cavern::from_input(&pods))
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord, Hash)]
-enum Pod {
+pub enum Pod {
     A,
     B,
     C,
@@ -72,8 +94,6 @@ impl Display for Pod {
     }
 }
 
-
-
 #[derive(Debug, Clone, Copy)]
 enum Loc {
     H(usize),
@@ -174,9 +194,7 @@ impl<const N: usize> Cavern<N> {
             Loc::H(h_idx) => self.move_to_room(pod, h_idx),
 
             // in a room, may move to hallway under some circumstances
-            Loc::R { col, row } => {
-                self.move_to_hallway(pod, col, row)
-            }
+            Loc::R { col, row } => self.move_to_hallway(pod, col, row),
         }
     }
 
@@ -319,6 +337,17 @@ impl<const N: usize> Display for Cavern<N> {
     }
 }
 
+impl<const N: usize> Render for Cavern<N> {
+    fn render(&self) -> Image {
+        render::from_text(&self.to_string(), |c| match c {
+            '#' => Rgb::GRAY,
+            '.' => Rgb::BLACK,
+            'A' | 'B' | 'C' | 'D' => Rgb::palette(c as usize - 'A' as usize),
+            _ => Rgb::WHITE,
+        })
+    }
+}
+
 #[derive(Clone)]
 enum PodStep {
     Empty,
@@ -370,77 +399,80 @@ impl Iterator for PodStep {
     }
 }
 
-fn move_all_pods_rec_cavern<const N: usize>(
-    cavern: Cavern<N>,
-    mut cache: &mut Map<Cavern<N>, usize>,
-    cost: usize,
-    min_cost: usize,
-) -> usize {
-    if let Some(c2) = cache.get(&cavern) {
-        if *c2 <= cost {
-            return min_cost;
-        }
-    }
+/// Moving the amphipods from `Cavern::from_input` to their rooms.
+struct Sorting<const N: usize>(Cavern<N>);
+
+impl<const N: usize> SearchProblem for Sorting<N> {
+    type State = Cavern<N>;
+    type Cost = usize;
 
-    cache.insert(cavern, cost);
-    if cavern.is_finished() {
-        return min_cost.min(cost);
+    fn start(&self) -> Cavern<N> {
+        self.0
     }
 
-    let mut min_cost = min_cost;
-    for (loc, _p) in cavern.pods() {
-        for (dest_idx, add_cost) in cavern.dests(loc) {
-            let g = cavern.move_pod(loc, dest_idx);
-            let c = cost + add_cost;
-            if c >= min_cost {
-                continue;
-            }
-            match cache.get(&g) {
-                Some(c2) if c2 > &c => {
-                    min_cost = min_cost.min(move_all_pods_rec_cavern(g, &mut cache, c, min_cost));
-                }
-                None => min_cost = min_cost.min(move_all_pods_rec_cavern(g, &mut cache, c, min_cost)),
-                _ => (),
+    fn successors(&self, cavern: &Cavern<N>, mut f: impl FnMut(Cavern<N>, usize)) {
+        for (loc, _p) in cavern.pods() {
+            for (dest, cost) in cavern.dests(loc) {
+                f(cavern.move_pod(loc, dest), cost);
             }
         }
     }
 
-    min_cost
+    fn is_goal(&self, cavern: &Cavern<N>) -> bool {
+        cavern.is_finished()
+    }
 }
 
-fn parse_input(raw: &str) -> [[Pod; 4]; 2] {
+// every amphipod moves at most twice, so the states are acyclic
+fn min_energy<const N: usize>(cavern: Cavern<N>) -> usize {
+    let path = search::dfs_memo(&Sorting(cavern))
+        .expect("two amphipods of each kind can always be sorted");
+    for (i, cavern) in path.states.iter().enumerate() {
+        trace::frame(i, || cavern);
+    }
+    path.cost
+}
+
+fn parse_input(raw: &str) -> Result<[[Pod; 4]; 2], ParseError> {
     use Pod::*;
-    raw.lines()
-        .skip(2)
-        .take(2)
-        .map(|l| {
-            l.chars()
-                .filter_map(|c| match c {
-                    'A' => Some(A),
-                    'B' => Some(B),
-                    'C' => Some(C),
-                    'D' => Some(D),
-                    _ => None,
-                })
-                .collect::<Vec<_>>()
-                .try_into()
-                .unwrap()
-        })
-        .collect::<Vec<_>>()
+    let mut rooms = Vec::new();
+    let mut count = [0; 4];
+    for line in raw.lines().skip(2).take(2) {
+        let mut row = Vec::new();
+        for (i, c) in line.char_indices() {
+            let pod = match c {
+                'A' => A,
+                'B' => B,
+                'C' => C,
+                'D' => D,
+                '#' | ' ' => continue,
+                _ => return Err(ParseError::at(raw, &line[i..], "`#` or an amphipod")),
+            };
+            count[pod.room_idx()] += 1;
+            row.push(pod);
+        }
+        let row: [Pod; 4] = row
+            .try_into()
+            .map_err(|_| ParseError::at(raw, line, "a row of 4 amphipods"))?;
+        rooms.push(row);
+    }
+    let rooms: [[Pod; 4]; 2] = rooms
         .try_into()
-        .unwrap()
+        .map_err(|_| ParseError::eof(raw, "two rows of amphipods"))?;
+
+    // otherwise the rooms can never be sorted
+    if count != [2; 4] {
+        let line = raw.lines().nth(2).unwrap();
+        return Err(ParseError::at(raw, line, "two amphipods of each kind"));
+    }
+    Ok(rooms)
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
-    const TEST_INPUT: &str = "#############
-#...........#
-###B#C#B#D###
-  #A#D#C#A#
-  #########
-";
+    const TEST_INPUT: &str = include_str!("../resources/examples/day23/example.txt");
 
     fn prn_cds<const N: usize>(c: &Cavern<N>, l: Loc) {
         let p = c.get(l).unwrap();
@@ -451,7 +483,7 @@ mod test {
     // #[test]
     // #[ignore]
     // fn test_cavern() {
-    //     let cavern = Cavern::from_input(&parse_input(TEST_INPUT));
+    //     let cavern = Cavern::from_input(&parse_input(TEST_INPUT).unwrap());
     //     println!("{}", cavern);
     //
     //     prn_cds(&cavern, Loc::R { col: 1, row: 0 });
@@ -473,7 +505,7 @@ mod test {
     #[ignore]
     fn test_stuff() {
         use Loc::*;
-        let parsed = parse_input(TEST_INPUT);
+        let parsed = parse_input(TEST_INPUT).unwrap();
         let cavern = Cavern::from_input(&parsed);
         println!("{}", cavern);
 
@@ -482,16 +514,29 @@ mod test {
         prn_cds(&cavern, H(0));
         prn_cds(&cavern, R { col: 1, row: 0 });
 
-        assert!(false);
+        panic!();
     }
 
     #[test]
     fn test_solve1() {
-        assert_eq!(12521, solve1(&parse_input(TEST_INPUT)))
+        assert_eq!(12521, solve1(&parse_input(TEST_INPUT).unwrap()))
     }
 
     #[test]
     fn test_solve2() {
-        assert_eq!(44169, solve2(&parse_input(TEST_INPUT)))
+        assert_eq!(44169, solve2(&parse_input(TEST_INPUT).unwrap()))
+    }
+
+    #[test]
+    fn test_path() {
+        let cavern = Cavern::from_input(&parse_input(TEST_INPUT).unwrap());
+        let path = search::dfs_memo(&Sorting(cavern)).unwrap();
+        assert_eq!(
+            Some(12521),
+            search::dijkstra(&Sorting(cavern)).map(|p| p.cost)
+        );
+        assert_eq!(12521, path.cost);
+        assert!(path.states[0] == cavern);
+        assert!(path.states.last().unwrap().is_finished());
     }
 }
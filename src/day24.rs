@@ -1,20 +1,45 @@
 use nom::branch::alt;
 use nom::bytes::complete::tag;
 use nom::character::complete as character;
-use nom::combinator::{all_consuming, map, opt};
+use nom::combinator::{map, opt};
 use nom::multi::separated_list1;
 use nom::sequence::{preceded, separated_pair, terminated};
 use nom::IResult;
 
-use crate::utils::parse_signed;
+use crate::utils::{check_lines, parse_all, parse_signed};
+use crate::{ParseError, Solution};
 
-pub fn solve() -> (isize, isize) {
-    let prog = parse_prog(include_str!("../resources/day24.txt"));
-    let constraints = get_constraints(&prog);
-    (
-        fold_digits(&solve1(&constraints)),
-        fold_digits(&solve2(&constraints)),
-    )
+pub struct Day24;
+
+impl Solution for Day24 {
+    const DAY: u8 = 24;
+    const INPUT: &'static str = include_str!("../resources/day24.txt");
+
+    type Input = Vec<Constraint>;
+    type Answer1 = isize;
+    type Answer2 = isize;
+
+    fn parse(raw: &str) -> Result<Self::Input, ParseError> {
+        get_constraints(&parse_prog(raw)?).map_err(|(i, expected)| match raw.lines().nth(i) {
+            Some(line) => ParseError::at(raw, line, expected),
+            None => ParseError::eof(raw, expected),
+        })
+    }
+
+    fn check(raw: &str) -> Vec<ParseError> {
+        match check_blocks(raw) {
+            errors if errors.is_empty() => Self::parse(raw).err().into_iter().collect(),
+            errors => errors,
+        }
+    }
+
+    fn part1(constraints: &Self::Input) -> Self::Answer1 {
+        fold_digits(&solve1(constraints))
+    }
+
+    fn part2(constraints: &Self::Input) -> Self::Answer2 {
+        fold_digits(&solve2(constraints))
+    }
 }
 
 fn solve1(constraints: &[Constraint]) -> [i8; 14] {
@@ -46,38 +71,83 @@ fn solve2(constraints: &[Constraint]) -> [i8; 14] {
 }
 
 /// (a, b, c) is read as input[a] = input[b] + c
-type Constraint = (usize, usize, i8);
+pub type Constraint = (usize, usize, i8);
+
+/// on error, returns the index of the unexpected instruction and what was
+/// expected instead
+fn get_constraints(prog: &[Ins]) -> Result<Vec<Constraint>, (usize, &'static str)> {
+    if prog.len() != 14 * 18 {
+        return Err((prog.len().min(14 * 18), "14 blocks of 18 instructions"));
+    }
 
-fn get_constraints(prog: &[Ins]) -> Vec<Constraint> {
     // grab some magic values
-    let xs = (0..14)
-        .into_iter()
-        .map(|i| get_ns(&prog[(i * 18 + 5) as usize]))
-        .collect::<Vec<_>>();
-    let ys = (0..14)
-        .into_iter()
-        .map(|i| get_ns(&prog[(i * 18 + 15) as usize]))
-        .collect::<Vec<_>>();
-    let zs = (0..14)
-        .into_iter()
-        .map(|i| get_ns(&prog[(i * 18 + 4) as usize]));
+    let magic = |offset: usize| {
+        (0..14)
+            .map(|i| i * 18 + offset)
+            .map(|i| get_ns(&prog[i]).ok_or((i, "an instruction with a number")))
+            .collect::<Result<Vec<_>, _>>()
+    };
+    let xs = magic(5)?;
+    let ys = magic(15)?;
+    let zs = magic(4)?;
 
     let mut result = vec![];
     let mut stack = vec![];
-    for (idx, z) in zs.enumerate() {
+    for (idx, z) in zs.into_iter().enumerate() {
         match z {
             1 => stack.push(idx),
             26 => {
-                let prev_idx = stack.pop().unwrap();
+                let prev_idx = stack.pop().ok_or((idx * 18 + 4, "`div z 1`"))?;
                 let cst = ys[prev_idx] + xs[idx];
                 let constraint = (idx, prev_idx, cst);
                 result.push(constraint);
             }
-            _ => unreachable!("invalid div z ? {}", z),
+            _ => return Err((idx * 18 + 4, "`div z 1` or `div z 26`")),
         }
     }
+    if !stack.is_empty() {
+        return Err((prog.len(), "as many `div z 26` as `div z 1`"));
+    }
 
-    result
+    Ok(result)
+}
+
+/// `get_constraints` assumes that the program is 14 copies of the same block,
+/// but for the numbers it grabs at offsets 4, 5 and 15
+fn check_blocks(raw: &str) -> Vec<ParseError> {
+    let mut errors = check_lines(raw, parse_ins, "an instruction like `add x 1`");
+    if !errors.is_empty() {
+        return errors;
+    }
+
+    let lines = raw.lines().collect::<Vec<_>>();
+    match lines.get(14 * 18) {
+        Some(line) => errors.push(ParseError::at(raw, line, "14 blocks of 18 instructions")),
+        None if lines.len() < 14 * 18 => {
+            errors.push(ParseError::eof(raw, "14 blocks of 18 instructions"))
+        }
+        None => (),
+    }
+    for (i, line) in lines.iter().enumerate().take(14 * 18) {
+        let template = lines[i % 18];
+        let ok = match i % 18 {
+            4 => *line == "div z 1" || *line == "div z 26",
+            5 | 15 => match (line.rsplit_once(' '), template.rsplit_once(' ')) {
+                (Some((ins, n)), Some((expected, _))) => ins == expected && n.parse::<i8>().is_ok(),
+                _ => false,
+            },
+            _ => *line == template,
+        };
+        if !ok {
+            let expected = match i % 18 {
+                4 => "`div z 1` or `div z 26`".to_string(),
+                5 | 15 => format!("`{}` with any number", template),
+                _ => format!("`{}`, like in the first block", template),
+            };
+            errors.push(ParseError::at(raw, line, expected));
+        }
+    }
+    errors
 }
 
 fn fold_digits(ds: &[i8]) -> isize {
@@ -85,17 +155,19 @@ fn fold_digits(ds: &[i8]) -> isize {
 }
 
 // grab hardcoded value in the bytecode
-fn get_ns(ins: &Ins) -> i8 {
+fn get_ns(ins: &Ins) -> Option<i8> {
     match ins {
-        Ins::Add(_, Var::N(n)) => *n,
-        Ins::Div(_, Var::N(n)) => *n,
-        Ins::Mul(_, Var::N(n)) => *n,
-        Ins::Mod(_, Var::N(n)) => *n,
-        Ins::Eql(_, Var::N(n)) => *n,
-        _ => unreachable!("oops {:?}", ins),
+        Ins::Add(_, Var::N(n)) => Some(*n),
+        Ins::Div(_, Var::N(n)) => Some(*n),
+        Ins::Mul(_, Var::N(n)) => Some(*n),
+        Ins::Mod(_, Var::N(n)) => Some(*n),
+        Ins::Eql(_, Var::N(n)) => Some(*n),
+        _ => None,
     }
 }
 
+// registers are only read when running the program, see the tests
+#[allow(dead_code)]
 #[derive(Debug)]
 enum Reg {
     W,
@@ -104,12 +176,14 @@ enum Reg {
     Z,
 }
 
+#[allow(dead_code)]
 #[derive(Debug)]
 enum Var {
     R(Reg),
     N(i8),
 }
 
+#[allow(dead_code)]
 #[derive(Debug)]
 enum Ins {
     Inp(Reg),
@@ -120,12 +194,15 @@ enum Ins {
     Eql(Reg, Var),
 }
 
-fn parse_prog(raw: &str) -> Vec<Ins> {
-    let r: IResult<&str, _> = all_consuming(terminated(
-        separated_list1(character::line_ending, parse_ins),
-        opt(character::line_ending),
-    ))(raw);
-    r.unwrap().1
+fn parse_prog(raw: &str) -> Result<Vec<Ins>, ParseError> {
+    parse_all(
+        raw,
+        terminated(
+            separated_list1(character::line_ending, parse_ins),
+            opt(character::line_ending),
+        ),
+        "an instruction like `add x 1`",
+    )
 }
 
 fn parse_ins(raw: &str) -> IResult<&str, Ins> {
@@ -254,14 +331,14 @@ mod test {
 
     #[test]
     fn test_parse() {
-        let prog = parse_prog(TEST_INPUT);
+        let prog = parse_prog(TEST_INPUT).unwrap();
         assert_eq!(252, prog.len());
     }
 
     #[test]
     fn test_solve1() {
-        let prog = parse_prog(TEST_INPUT);
-        let inputs = solve1(&get_constraints(&prog));
+        let prog = parse_prog(TEST_INPUT).unwrap();
+        let inputs = solve1(&get_constraints(&prog).unwrap());
         let mut alu = Alu::default();
         alu.run_prog(&inputs, &prog);
         assert_eq!(alu.regs[3], 0);
@@ -269,11 +346,25 @@ mod test {
 
     #[test]
     fn test_solve2() {
-        let prog = parse_prog(TEST_INPUT);
-        let inputs = solve2(&get_constraints(&prog));
+        let prog = parse_prog(TEST_INPUT).unwrap();
+        let inputs = solve2(&get_constraints(&prog).unwrap());
         let mut alu = Alu::default();
         alu.run_prog(&inputs, &prog);
         assert_eq!(alu.regs[3], 0);
     }
 
+    #[test]
+    fn test_check() {
+        assert!(Day24::check(TEST_INPUT).is_empty());
+        // an unexpected instruction in the second block, a bad division in
+        // the fourth
+        let mut lines = TEST_INPUT.lines().collect::<Vec<_>>();
+        lines[18 + 9] = "add y 24";
+        lines[3 * 18 + 4] = "div z 25";
+        let raw = lines.join("\n");
+        let errors = Day24::check(&raw);
+        assert_eq!(2, errors.len(), "{:?}", errors);
+        assert_eq!((28, 59), (errors[0].line, errors[1].line));
+        assert_eq!("`div z 1` or `div z 26`", errors[1].expected);
+    }
 }
@@ -1,24 +1,59 @@
 use std::fmt::Display;
 
-pub fn solve() -> (usize, &'static str) {
-    let grid = Grid::from_str(include_str!("../resources/day25.txt"));
-    (solve1(grid), "star!")
+use crate::geom::Point2;
+use crate::render::{Image, Render, Rgb};
+use crate::trace;
+use crate::utils::check_grid;
+use crate::{ParseError, Solution};
+
+pub struct Day25;
+
+impl Solution for Day25 {
+    const DAY: u8 = 25;
+    const INPUT: &'static str = include_str!("../resources/day25.txt");
+
+    type Input = Grid;
+    type Answer1 = usize;
+    type Answer2 = &'static str;
+
+    fn parse(raw: &str) -> Result<Self::Input, ParseError> {
+        parse_grid(raw)
+    }
+
+    fn check(raw: &str) -> Vec<ParseError> {
+        let is_cell = |c| matches!(c, '>' | 'v' | '.');
+        check_grid(raw, raw.lines(), None, is_cell, "`>`, `v` or `.`")
+    }
+
+    fn render(grid: &Self::Input) -> Option<Image> {
+        Some(Herds(grid).render())
+    }
+
+    fn part1(grid: &Self::Input) -> Self::Answer1 {
+        solve1(grid)
+    }
+
+    fn part2(_grid: &Self::Input) -> Self::Answer2 {
+        "star!"
+    }
 }
 
-fn solve1(grid: Grid) -> usize {
-    let mut has_moved = true;
-    let mut n = 0;
-    let mut grid = grid;
+fn solve1(grid: &Grid) -> usize {
+    steps_to_settle(grid, usize::MAX).expect("the sea cucumbers stop moving")
+}
+
+/// the first step on which no sea cucumber moves, if it is within `max`
+/// steps
+pub(crate) fn steps_to_settle(grid: &Grid, max: usize) -> Option<usize> {
+    let mut grid = grid.clone();
     let mut next_grid = grid.clone();
 
-    while has_moved {
-        n += 1;
-        has_moved = false;
+    trace::frame(0, || Herds(&grid));
+    for n in 1..=max {
+        let mut has_moved = false;
 
         for dir in [Dir::East, Dir::South] {
-            let idx_to_move = (0..grid.points.len())
-                .into_iter()
-                .filter_map(|i| grid.can_move(i, dir));
+            let idx_to_move = (0..grid.points.len()).filter_map(|i| grid.can_move(i, dir));
 
             for (i, j) in idx_to_move {
                 has_moved = true;
@@ -29,12 +64,40 @@ fn solve1(grid: Grid) -> usize {
             grid = next_grid;
             next_grid = grid.clone();
         }
+        trace::frame(n, || Herds(&grid));
+        if !has_moved {
+            return Some(n);
+        }
+    }
+    None
+}
+
+/// the sea floor, as in the puzzle
+struct Herds<'a>(&'a Grid);
+
+impl Display for Herds<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cell = |c: &Option<Dir>| match c {
+            Some(Dir::East) => '>',
+            Some(Dir::South) => 'v',
+            None => '.',
+        };
+        write!(f, "{}", self.0.map(cell))
+    }
+}
+
+impl Render for Herds<'_> {
+    fn render(&self) -> Image {
+        self.0.map(|c| match c {
+            Some(Dir::East) => Rgb::palette(0),
+            Some(Dir::South) => Rgb::palette(1),
+            None => Rgb::BLACK,
+        })
     }
-    n
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Dir {
+pub enum Dir {
     East,
     South,
 }
@@ -71,77 +134,34 @@ impl std::ops::Not for Dir {
     }
 }
 
-#[derive(Debug, Clone)]
-struct Grid {
-    width: usize,
-    height: usize,
-    points: Vec<Option<Dir>>,
-}
-
-impl Display for Grid {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for j in 0..self.height {
-            for i in 0..self.width {
-                let idx = j * self.width + i;
-                match &self.points[idx] {
-                    Some(d) => write!(f, "{}", d)?,
-                    None => f.write_str(".")?,
-                }
-            }
-            f.write_str("\n")?;
-        }
-        Ok(())
-    }
+pub type Grid = crate::utils::Grid<Option<Dir>>;
+
+fn parse_grid(raw: &str) -> Result<Grid, ParseError> {
+    Grid::from_str(
+        raw,
+        |c| match c {
+            '.' => Some(None),
+            _ => c.try_into().ok().map(Some),
+        },
+        "`>`, `v` or `.`",
+    )
 }
 
 impl Grid {
-    fn from_str(raw: &str) -> Self {
-        let mut points = Vec::new();
-        let mut width = 0;
-        let mut height = 0;
-        for line in raw.lines() {
-            width = line.len();
-            height += 1;
-            for c in line.chars() {
-                points.push(c.try_into().ok());
-            }
-        }
-        Self {
-            width,
-            height,
-            points,
-        }
-    }
-
     // if can move, returns Some(idx, next_idx)
     fn can_move(&self, idx: usize, dir: Dir) -> Option<(usize, usize)> {
-        let y = idx / self.width;
-        let x = idx % self.width;
-        // println!("idx: {} - ({},{})", idx, x, y);
-        match self.points[idx] {
-            Some(Dir::East) if dir == Dir::East => {
-                let next_idx = if x == self.width - 1 {
-                    idx + 1 - self.width
-                } else {
-                    idx + 1
-                };
-                match self.points[next_idx] {
-                    None => Some((idx, next_idx)),
-                    Some(_) => None,
-                }
-            }
-            Some(Dir::South) if dir == Dir::South => {
-                let next_idx = if y == self.height - 1 {
-                    x
-                } else {
-                    idx + self.width
-                };
-                match self.points[next_idx] {
-                    None => Some((idx, next_idx)),
-                    Some(_) => None,
-                }
-            }
-            _ => None,
+        if self.points[idx] != Some(dir) {
+            return None;
+        }
+        let Point2 { x, y } = self.point(idx);
+        let next = match dir {
+            Dir::East => self.wrapping_offset(x, y, 1, 0),
+            Dir::South => self.wrapping_offset(x, y, 0, 1),
+        };
+        let next_idx = self.idx(next);
+        match self.points[next_idx] {
+            None => Some((idx, next_idx)),
+            Some(_) => None,
         }
     }
 }
@@ -150,20 +170,29 @@ impl Grid {
 mod test {
     use super::*;
 
-    const TEST_INPUT: &str = "v...>>.vv>
-.vv>>.vv..
->>.>v>...v
->>v>>.>.v.
-v>v.vv.v..
->.>>..v...
-.vv..>.>v.
-v.v..>>v.v
-....v..v.>
-";
+    const TEST_INPUT: &str = include_str!("../resources/examples/day25/example.txt");
 
     #[test]
     fn test_solve1() {
-        let grid = Grid::from_str(TEST_INPUT);
-        assert_eq!(58, solve1(grid));
+        let grid = parse_grid(TEST_INPUT).unwrap();
+        assert_eq!(58, solve1(&grid));
+        // going round and round
+        let grid = parse_grid(">.\n").unwrap();
+        assert_eq!(None, steps_to_settle(&grid, 100));
+    }
+
+    #[test]
+    fn test_can_move() {
+        let grid = parse_grid("..v\n>.>\n").unwrap();
+        assert_eq!(Some((3, 4)), grid.can_move(3, Dir::East));
+        assert_eq!(None, grid.can_move(5, Dir::East));
+        assert_eq!(None, grid.can_move(3, Dir::South));
+        assert_eq!(None, grid.can_move(2, Dir::South));
+
+        // both directions wrap around
+        let grid = parse_grid("...\n..v\n").unwrap();
+        assert_eq!(Some((5, 2)), grid.can_move(5, Dir::South));
+        let grid = parse_grid("..>\n").unwrap();
+        assert_eq!(Some((2, 0)), grid.can_move(2, Dir::East));
     }
 }
@@ -0,0 +1,151 @@
+use std::fmt::Display;
+
+/// Location and description of what went wrong while parsing a puzzle input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based column, counted in characters
+    pub column: usize,
+    pub expected: String,
+    /// the offending line, used when displaying the error
+    pub snippet: String,
+}
+
+impl ParseError {
+    /// `rest` must be a slice of `input`, starting where parsing failed.
+    /// This is what nom returns in its errors.
+    pub fn at(input: &str, rest: &str, expected: impl Into<String>) -> Self {
+        let start = input.as_ptr() as usize;
+        let offset = (rest.as_ptr() as usize)
+            .checked_sub(start)
+            .filter(|o| *o <= input.len())
+            .expect("rest is a slice of input");
+
+        let line_start = input[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let line_end = input[offset..]
+            .find('\n')
+            .map(|i| i + offset)
+            .unwrap_or(input.len());
+
+        Self {
+            line: input[..line_start].matches('\n').count() + 1,
+            column: input[line_start..offset].chars().count() + 1,
+            expected: expected.into(),
+            snippet: input[line_start..line_end]
+                .trim_end_matches('\r')
+                .to_string(),
+        }
+    }
+
+    /// error located at the very end of `input`
+    pub fn eof(input: &str, expected: impl Into<String>) -> Self {
+        Self::at(input, &input[input.len()..], expected)
+    }
+
+    /// convert a nom error, `input` being the whole text given to the parser
+    pub fn from_nom(
+        input: &str,
+        err: nom::Err<nom::error::Error<&str>>,
+        expected: impl Into<String>,
+    ) -> Self {
+        match err {
+            nom::Err::Error(e) | nom::Err::Failure(e) => Self::at(input, e.input, expected),
+            nom::Err::Incomplete(_) => Self::eof(input, expected),
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: expected {}",
+            self.line, self.column, self.expected
+        )?;
+        if !self.snippet.is_empty() {
+            write!(
+                f,
+                "\n  {}\n  {:>width$}",
+                self.snippet,
+                "^",
+                width = self.column
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Crate wide error type.
+#[derive(Debug)]
+pub enum Error {
+    UnknownDay(u8),
+    Parse {
+        day: u8,
+        error: ParseError,
+    },
+    Io {
+        path: String,
+        error: std::io::Error,
+    },
+    /// a data file used by the runner, like the expected answers, is
+    /// malformed
+    File {
+        path: String,
+        error: ParseError,
+    },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::UnknownDay(day) => write!(f, "invalid day {}", day),
+            Error::Parse { day, error } => write!(f, "day{:02}: invalid input at {}", day, error),
+            Error::Io { path, error } => write!(f, "{}: {}", path, error),
+            Error::File { path, error } => write!(f, "{}: invalid content at {}", path, error),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::UnknownDay(_) => None,
+            Error::Parse { error, .. } => Some(error),
+            Error::Io { error, .. } => Some(error),
+            Error::File { error, .. } => Some(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_location() {
+        let input = "abc\ndéf\nghi\n";
+        let err = ParseError::at(input, &input[7..], "something");
+        assert_eq!((2, 3), (err.line, err.column));
+        assert_eq!("déf", err.snippet);
+
+        let err = ParseError::at(input, &input[4..], "something");
+        assert_eq!((2, 1), (err.line, err.column));
+
+        let err = ParseError::eof(input, "something");
+        assert_eq!((4, 1), (err.line, err.column));
+        assert_eq!("", err.snippet);
+    }
+
+    #[test]
+    fn test_display() {
+        let input = "1,2\n3;4\n";
+        let err = ParseError::at(input, &input[5..], "`,`");
+        assert_eq!(
+            "line 2, column 2: expected `,`\n  3;4\n   ^",
+            err.to_string()
+        );
+    }
+}
@@ -0,0 +1,787 @@
+//! Random puzzle inputs of arbitrary size, in the exact format of each day,
+//! to stress-test the solvers.
+//!
+//! The same day, size and seed always give the same input. What the size is
+//! depends on the day:
+//!
+//! | day | size                           | day | size                         |
+//! |-----|--------------------------------|-----|------------------------------|
+//! |  1  | depths                         | 14  | length of the template       |
+//! |  2  | commands                       | 15  | side of the grid             |
+//! |  3  | diagnostic numbers             | 16  | packets                      |
+//! |  4  | boards                         | 17  | depth of the target area     |
+//! |  5  | lines of vents                 | 18  | snailfish numbers            |
+//! |  6  | lanternfishes                  | 19  | scanners, at most 256        |
+//! |  7  | crabs                          | 20  | side of the image            |
+//! |  8  | displays                       | 21  | unused                       |
+//! |  9  | side of the heightmap, from 2  | 22  | reboot steps                 |
+//! | 10  | lines of chunks                | 23  | unused                       |
+//! | 11  | side of the grid               | 24  | unused                       |
+//! | 12  | caves, at most 62              | 25  | side of the grid             |
+//! | 13  | dots                           |     |                              |
+
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+use crate::day11;
+use crate::day25;
+use crate::geom::{Point3, M3};
+use crate::Solution;
+
+/// xorshift64*, good enough to generate inputs
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // the state must never be 0
+        Self(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// uniform in `0..n`
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// uniform in `lo..=hi`
+    pub fn between(&mut self, lo: i32, hi: i32) -> i32 {
+        lo + self.below((hi - lo + 1) as usize) as i32
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+/// a size close to the one of the real inputs
+pub fn default_size(day: u8) -> usize {
+    match day {
+        1 => 2000,
+        2 | 3 | 7 => 1000,
+        4 | 9 | 10 | 15 | 18 | 20 => 100,
+        5 => 500,
+        6 => 300,
+        8 => 200,
+        11 | 12 => 10,
+        13 => 800,
+        14 => 20,
+        16 => 300,
+        17 => 100,
+        19 => 30,
+        22 => 420,
+        25 => 139,
+        _ => 1,
+    }
+}
+
+/// `None` when `day` isn't between 1 and 25
+pub fn generate(day: u8, size: usize, seed: u64) -> Option<String> {
+    // mixing the day in, so that the days don't all start alike
+    let mut rng = Rng::new(seed ^ ((day as u64) << 56));
+    let rng = &mut rng;
+    let size = size.max(1);
+    let mut out = String::new();
+    let w = &mut out;
+    match day {
+        1 => day01(rng, size, w),
+        2 => day02(rng, size, w),
+        3 => day03(rng, size, w),
+        4 => day04(rng, size, w),
+        5 => day05(rng, size, w),
+        6 => day06(rng, size, w),
+        7 => day07(rng, size, w),
+        8 => day08(rng, size, w),
+        9 => day09(rng, size, w),
+        10 => day10(rng, size, w),
+        11 => day11(rng, size, w),
+        12 => day12(rng, size, w),
+        13 => day13(rng, size, w),
+        14 => day14(rng, size, w),
+        15 => day15(rng, size, w),
+        16 => day16(rng, size, w),
+        17 => day17(rng, size, w),
+        18 => day18(rng, size, w),
+        19 => day19(rng, size, w),
+        20 => day20(rng, size, w),
+        21 => day21(rng, w),
+        22 => day22(rng, size, w),
+        23 => day23(rng, w),
+        24 => day24(rng, w),
+        25 => day25(rng, size, w),
+        _ => return None,
+    }
+    .expect("writing to a String never fails");
+    Some(out)
+}
+
+type Result = std::fmt::Result;
+
+fn join(items: impl Iterator<Item = impl ToString>, sep: &str) -> String {
+    items.map(|i| i.to_string()).collect::<Vec<_>>().join(sep)
+}
+
+/// `width` x `height` cells, one row per line
+fn grid(w: &mut String, width: usize, height: usize, mut cell: impl FnMut() -> char) -> Result {
+    for _ in 0..height {
+        let row = (0..width).map(|_| cell()).collect::<String>();
+        writeln!(w, "{}", row)?;
+    }
+    Ok(())
+}
+
+fn day01(rng: &mut Rng, size: usize, w: &mut String) -> Result {
+    let mut depth = 100;
+    for _ in 0..size {
+        depth = (depth + rng.below(21)).saturating_sub(9);
+        writeln!(w, "{}", depth)?;
+    }
+    Ok(())
+}
+
+fn day02(rng: &mut Rng, size: usize, w: &mut String) -> Result {
+    // never go up above the surface, nor get a negative aim
+    let mut depth = 0;
+    for _ in 0..size {
+        let n = 1 + rng.below(9);
+        let cmd = match rng.below(3) {
+            0 => "forward",
+            1 if depth >= n => {
+                depth -= n;
+                "up"
+            }
+            _ => {
+                depth += n;
+                "down"
+            }
+        };
+        writeln!(w, "{} {}", cmd, n)?;
+    }
+    Ok(())
+}
+
+fn day03(rng: &mut Rng, size: usize, w: &mut String) -> Result {
+    // distinct numbers, using about a quarter of the possible ones
+    let width = (usize::BITS - (4 * size).leading_zeros()).max(5) as usize;
+    let mut nums = BTreeSet::new();
+    while nums.len() < size {
+        nums.insert(rng.below(1 << width));
+    }
+    let mut nums = nums.into_iter().collect::<Vec<_>>();
+    rng.shuffle(&mut nums);
+    for n in nums {
+        writeln!(w, "{:0width$b}", n, width = width)?;
+    }
+    Ok(())
+}
+
+fn day04(rng: &mut Rng, size: usize, w: &mut String) -> Result {
+    // all the numbers are drawn, so that every board wins
+    let mut nums = (0..100).collect::<Vec<u8>>();
+    rng.shuffle(&mut nums);
+    writeln!(w, "{}", join(nums.iter(), ","))?;
+    for _ in 0..size {
+        rng.shuffle(&mut nums);
+        writeln!(w)?;
+        for row in nums[..25].chunks(5) {
+            writeln!(w, "{}", join(row.iter().map(|n| format!("{:2}", n)), " "))?;
+        }
+    }
+    Ok(())
+}
+
+fn day05(rng: &mut Rng, size: usize, w: &mut String) -> Result {
+    for _ in 0..size {
+        let (x1, y1) = (rng.between(0, 988), rng.between(0, 988));
+        let len = rng.between(1, 989 - x1.max(y1)).min(300);
+        let (x2, y2) = match rng.below(3) {
+            0 => (x1 + len, y1),
+            1 => (x1, y1 + len),
+            _ => (x1 + len, y1 + len),
+        };
+        // going either way, and flipping diagonals to go both ways too
+        let (y1, y2) = if rng.below(2) == 0 {
+            (y1, y2)
+        } else {
+            (y2, y1)
+        };
+        if rng.below(2) == 0 {
+            writeln!(w, "{},{} -> {},{}", x1, y1, x2, y2)?;
+        } else {
+            writeln!(w, "{},{} -> {},{}", x2, y2, x1, y1)?;
+        }
+    }
+    Ok(())
+}
+
+fn day06(rng: &mut Rng, size: usize, w: &mut String) -> Result {
+    writeln!(w, "{}", join((0..size).map(|_| 1 + rng.below(5)), ","))
+}
+
+fn day07(rng: &mut Rng, size: usize, w: &mut String) -> Result {
+    writeln!(w, "{}", join((0..size).map(|_| rng.below(2000)), ","))
+}
+
+fn day08(rng: &mut Rng, size: usize, w: &mut String) -> Result {
+    const DIGITS: [&str; 10] = [
+        "abcefg", "cf", "acdeg", "acdfg", "bcdf", "abdfg", "abdefg", "acf", "abcdefg", "abcdfg",
+    ];
+    for _ in 0..size {
+        let mut wires = *b"abcdefg";
+        rng.shuffle(&mut wires);
+        let scramble = |rng: &mut Rng, digit: usize| {
+            let mut segments = DIGITS[digit]
+                .bytes()
+                .map(|s| wires[(s - b'a') as usize] as char)
+                .collect::<Vec<_>>();
+            rng.shuffle(&mut segments);
+            segments.into_iter().collect::<String>()
+        };
+        let mut digits = (0..10).collect::<Vec<_>>();
+        rng.shuffle(&mut digits);
+        let patterns = digits.into_iter().map(|d| scramble(rng, d));
+        let patterns = patterns.collect::<Vec<_>>();
+        let output = (0..4).map(|_| {
+            let digit = rng.below(10);
+            scramble(rng, digit)
+        });
+        let output = output.collect::<Vec<_>>();
+        writeln!(w, "{} | {}", patterns.join(" "), output.join(" "))?;
+    }
+    Ok(())
+}
+
+fn day09(rng: &mut Rng, size: usize, w: &mut String) -> Result {
+    // Basins around centres on a jittered lattice, with walls of 9 where two
+    // of them meet. Uniform digits would make one huge basin instead.
+    const SPACING: usize = 7;
+    let size = size.max(2);
+    let cells = size.div_ceil(SPACING) + 1;
+    let centres = (0..cells * cells)
+        .map(|i| {
+            let jitter = |rng: &mut Rng| rng.between(-1, 1) as isize;
+            let x = (i % cells * SPACING + SPACING / 2) as isize + jitter(rng);
+            let y = (i / cells * SPACING + SPACING / 2) as isize + jitter(rng);
+            (x, y)
+        })
+        .collect::<Vec<_>>();
+    for y in 0..size {
+        let row = (0..size).map(|x| {
+            let (cx, cy) = (x / SPACING, y / SPACING);
+            let mut dists = (cy.saturating_sub(2)..(cy + 3).min(cells))
+                .flat_map(|j| (cx.saturating_sub(2)..(cx + 3).min(cells)).map(move |i| (i, j)))
+                .map(|(i, j)| {
+                    let (px, py) = centres[j * cells + i];
+                    (px - x as isize).abs() + (py - y as isize).abs()
+                })
+                .collect::<Vec<_>>();
+            dists.sort_unstable();
+            if dists[1] - dists[0] <= 1 {
+                '9'
+            } else {
+                char::from(b'0' + dists[0].min(8) as u8)
+            }
+        });
+        writeln!(w, "{}", row.collect::<String>())?;
+    }
+    Ok(())
+}
+
+fn day10(rng: &mut Rng, size: usize, w: &mut String) -> Result {
+    const PAIRS: [(char, char); 4] = [('(', ')'), ('[', ']'), ('{', '}'), ('<', '>')];
+    for i in 0..size {
+        // the first line is always incomplete, so that part 2 has a middle
+        let corrupted = i > 0 && rng.below(2) == 0;
+        let len = 80 + rng.below(30);
+        let corrupt_at = rng.below(len);
+        let mut stack = vec![];
+        let mut line = String::new();
+        for j in 0..len {
+            // closing the incomplete lines too deep would overflow their score
+            if !stack.is_empty() && (stack.len() >= 20 || rng.below(2) == 0) {
+                let close: char = stack.pop().unwrap();
+                if corrupted && j >= corrupt_at {
+                    let wrong = PAIRS.iter().map(|p| p.1).filter(|c| *c != close);
+                    line.push(*rng.choose(&wrong.collect::<Vec<_>>()));
+                    line.extend((0..rng.below(10)).map(|_| rng.choose(&PAIRS).0));
+                    break;
+                }
+                line.push(close);
+            } else {
+                let (open, close) = *rng.choose(&PAIRS);
+                stack.push(close);
+                line.push(open);
+            }
+        }
+        if stack.is_empty() {
+            line.push(rng.choose(&PAIRS).0);
+        }
+        writeln!(w, "{}", line)?;
+    }
+    Ok(())
+}
+
+fn day11(rng: &mut Rng, size: usize, w: &mut String) -> Result {
+    // Most random grids never synchronise, and part 2 would never end. Small
+    // ones do often enough to retry, larger ones get octopuses with two
+    // energy levels only, which all flash as soon as the highest ones do.
+    if size <= 20 {
+        for _ in 0..100 {
+            let octopuses = day11::Grid::from_fn(size, size, |_, _| rng.below(10) as u8);
+            if day11::steps_to_sync(&octopuses, 1000).is_some() {
+                return write!(w, "{}", octopuses);
+            }
+        }
+    }
+    let low = rng.below(9) as u8;
+    grid(w, size, size, || {
+        char::from(b'0' + low + rng.below(2) as u8)
+    })
+}
+
+fn day12(rng: &mut Rng, size: usize, w: &mut String) -> Result {
+    // two big caves are never connected, otherwise there would be infinitely
+    // many paths
+    let size = size.min(62);
+    let big = size / 4;
+    let mut caves = vec!["start".to_string(), "end".to_string()];
+    let mut names = BTreeSet::new();
+    while names.len() < size {
+        let name = (0..2)
+            .map(|_| char::from(b'a' + rng.below(26) as u8))
+            .collect::<String>();
+        names.insert(name);
+    }
+    let mut others = names
+        .into_iter()
+        .enumerate()
+        .map(|(i, n)| if i < big { n.to_uppercase() } else { n })
+        .collect::<Vec<_>>();
+    rng.shuffle(&mut others);
+    caves.extend(others);
+    let is_big = |i: usize| caves[i].chars().all(|c| c.is_ascii_uppercase());
+
+    // a random tree, so that every cave is reachable, and a few more edges,
+    // not more with the size: every cycle multiplies the paths of part 2
+    let mut edges = BTreeSet::new();
+    for i in 1..caves.len() {
+        let mut j = rng.below(i);
+        while is_big(i) && is_big(j) {
+            j = rng.below(i);
+        }
+        edges.insert((j, i));
+    }
+    for _ in 0..size.min(6) {
+        let (i, j) = (rng.below(caves.len()), rng.below(caves.len()));
+        if i != j && !(is_big(i) && is_big(j)) && !edges.contains(&(j, i)) {
+            edges.insert((i, j));
+        }
+    }
+    let mut edges = edges.into_iter().collect::<Vec<_>>();
+    rng.shuffle(&mut edges);
+    for (i, j) in edges {
+        writeln!(w, "{}-{}", caves[i], caves[j])?;
+    }
+    Ok(())
+}
+
+fn day13(rng: &mut Rng, size: usize, w: &mut String) -> Result {
+    // start from the folded paper, and unfold it, mirroring dots at random
+    let (mut width, mut height) = (40, 6);
+    let mut dots = (0..size)
+        .map(|_| (rng.below(width), rng.below(height)))
+        .collect::<Vec<_>>();
+    let mut unfolds = [vec!['x'; 5], vec!['y'; 7]].concat();
+    rng.shuffle(&mut unfolds);
+    let mut folds = vec![];
+    for axis in unfolds {
+        let line = if axis == 'x' { &mut width } else { &mut height };
+        let at = *line;
+        for (x, y) in dots.iter_mut() {
+            let coord = if axis == 'x' { x } else { y };
+            if rng.below(2) == 0 {
+                *coord = 2 * at - *coord;
+            }
+        }
+        *line = 2 * at + 1;
+        folds.push((axis, at));
+    }
+    let dots = dots.into_iter().collect::<BTreeSet<_>>();
+    let mut dots = dots.into_iter().collect::<Vec<_>>();
+    rng.shuffle(&mut dots);
+    for (x, y) in dots {
+        writeln!(w, "{},{}", x, y)?;
+    }
+    writeln!(w)?;
+    for (axis, at) in folds.into_iter().rev() {
+        writeln!(w, "fold along {}={}", axis, at)?;
+    }
+    Ok(())
+}
+
+fn day14(rng: &mut Rng, size: usize, w: &mut String) -> Result {
+    const ELEMENTS: &[u8] = b"BCFHKNOPSV";
+    let element = |rng: &mut Rng| char::from(*rng.choose(ELEMENTS));
+    let template = (0..size.max(2)).map(|_| element(rng)).collect::<String>();
+    writeln!(w, "{}\n", template)?;
+    for a in ELEMENTS {
+        for b in ELEMENTS {
+            writeln!(w, "{}{} -> {}", *a as char, *b as char, element(rng))?;
+        }
+    }
+    Ok(())
+}
+
+fn day15(rng: &mut Rng, size: usize, w: &mut String) -> Result {
+    grid(w, size, size, || char::from(b'1' + rng.below(9) as u8))
+}
+
+/// BITS packet, see [`day16`]
+enum Packet {
+    Literal(u64),
+    Operator(u8, Vec<Packet>),
+}
+
+impl Packet {
+    /// `size` packets in total. Products only get small values, so that
+    /// the result fits in a u64.
+    fn random(rng: &mut Rng, size: usize, small: bool) -> Self {
+        if size < 3 || (small && size < 5) {
+            let max = if small { 1000 } else { 1 << 20 };
+            return Packet::Literal(rng.below(max) as u64);
+        }
+        // comparisons are 5, 6 and 7, and have exactly two sub-packets
+        let type_id = if small {
+            5 + rng.below(3)
+        } else {
+            rng.below(7)
+        };
+        let type_id = if type_id == 4 { 7 } else { type_id as u8 };
+        let count = match type_id {
+            1 => 2 + rng.below(2),
+            5..=7 => 2,
+            _ => 2 + rng.below(6),
+        }
+        .min(size - 1);
+        let subs = (0..count)
+            .map(|i| {
+                let share = (size - 1) / count + usize::from(i < (size - 1) % count);
+                Packet::random(rng, share, type_id == 1)
+            })
+            .collect();
+        Packet::Operator(type_id, subs)
+    }
+
+    fn encode(&self, rng: &mut Rng, bits: &mut Vec<bool>) {
+        let push = |bits: &mut Vec<bool>, n: u64, len: usize| {
+            bits.extend((0..len).rev().map(|i| n >> i & 1 == 1));
+        };
+        push(bits, rng.below(8) as u64, 3);
+        match self {
+            Packet::Literal(n) => {
+                push(bits, 4, 3);
+                let groups = (64 - n.leading_zeros() as usize).div_ceil(4).max(1);
+                for g in (0..groups).rev() {
+                    bits.push(g > 0);
+                    push(bits, n >> (4 * g) & 0xF, 4);
+                }
+            }
+            Packet::Operator(type_id, subs) => {
+                push(bits, *type_id as u64, 3);
+                let mut sub_bits = vec![];
+                for sub in subs {
+                    sub.encode(rng, &mut sub_bits);
+                }
+                if sub_bits.len() < 1 << 15 && rng.below(2) == 0 {
+                    bits.push(false);
+                    push(bits, sub_bits.len() as u64, 15);
+                } else {
+                    bits.push(true);
+                    push(bits, subs.len() as u64, 11);
+                }
+                bits.extend(sub_bits);
+            }
+        }
+    }
+}
+
+fn day16(rng: &mut Rng, size: usize, w: &mut String) -> Result {
+    let mut bits = vec![];
+    Packet::random(rng, size, false).encode(rng, &mut bits);
+    // whole bytes, padded with zeros
+    bits.resize(bits.len().div_ceil(8) * 8, false);
+    for nibble in bits.chunks(4) {
+        let n = nibble.iter().fold(0, |acc, b| acc * 2 + u32::from(*b));
+        write!(
+            w,
+            "{}",
+            char::from_digit(n, 16).unwrap().to_ascii_uppercase()
+        )?;
+    }
+    writeln!(w)
+}
+
+fn day17(rng: &mut Rng, size: usize, w: &mut String) -> Result {
+    let depth = size.max(5) as i32;
+    let x0 = rng.between(depth, 2 * depth);
+    let x1 = x0 + rng.between(depth / 5, depth / 2);
+    let y1 = -depth;
+    let y0 = y1 + rng.between(depth / 5, depth / 2);
+    writeln!(w, "target area: x={}..{}, y={}..{}", x0, x1, y1, y0)
+}
+
+fn snailfish(rng: &mut Rng, depth: usize) -> String {
+    // pairs are at most nested 4 deep in reduced numbers
+    if depth == 4 || (depth > 1 && rng.below(3) == 0) {
+        return rng.below(10).to_string();
+    }
+    format!(
+        "[{},{}]",
+        snailfish(rng, depth + 1),
+        snailfish(rng, depth + 1)
+    )
+}
+
+fn day18(rng: &mut Rng, size: usize, w: &mut String) -> Result {
+    for _ in 0..size {
+        writeln!(w, "{}", snailfish(rng, 0))?;
+    }
+    Ok(())
+}
+
+fn day19(rng: &mut Rng, size: usize, w: &mut String) -> Result {
+    // Scanners along the x axis, 1300 apart, so that each one only overlaps
+    // with the previous and the next one. Each overlap gets 13 beacons, and
+    // each scanner a few more that only it can see.
+    const RANGE: i32 = 1000;
+    let size = size.min(256);
+    let scanners = (0..size as i32)
+        .map(|i| {
+            let x = i * 1300 + rng.between(-50, 50);
+            Point3::new(x, rng.between(-100, 100), rng.between(-100, 100))
+        })
+        .collect::<Vec<_>>();
+    let random_in = |rng: &mut Rng, min: Point3<i32>, max: Point3<i32>| {
+        Point3::new(
+            rng.between(min.x, max.x),
+            rng.between(min.y, max.y),
+            rng.between(min.z, max.z),
+        )
+    };
+    let mut beacons = BTreeSet::new();
+    for (i, s) in scanners.iter().enumerate() {
+        let own = Point3::new(150, RANGE, RANGE);
+        for _ in 0..2 + rng.below(5) {
+            beacons.insert(random_in(rng, *s - own, *s + own));
+        }
+        if let Some(next) = scanners.get(i + 1) {
+            let (y0, y1) = (s.y.max(next.y) - RANGE, s.y.min(next.y) + RANGE);
+            let (z0, z1) = (s.z.max(next.z) - RANGE, s.z.min(next.z) + RANGE);
+            let min = Point3::new(next.x - RANGE, y0, z0);
+            let max = Point3::new(s.x + RANGE, y1, z1);
+            let overlap = beacons.len() + 13;
+            while beacons.len() < overlap {
+                beacons.insert(random_in(rng, min, max));
+            }
+        }
+    }
+
+    let rotations = M3::rotations().collect::<Vec<_>>();
+    for (i, s) in scanners.iter().enumerate() {
+        let rotation = *rng.choose(&rotations);
+        let mut seen = beacons
+            .iter()
+            .map(|b| *b - *s)
+            .filter(|b| b.x.abs() <= RANGE && b.y.abs() <= RANGE && b.z.abs() <= RANGE)
+            .map(|b| rotation * b)
+            .collect::<Vec<_>>();
+        rng.shuffle(&mut seen);
+        if i > 0 {
+            writeln!(w)?;
+        }
+        writeln!(w, "--- scanner {} ---", i)?;
+        for b in seen {
+            writeln!(w, "{},{},{}", b.x, b.y, b.z)?;
+        }
+    }
+    Ok(())
+}
+
+fn day20(rng: &mut Rng, size: usize, w: &mut String) -> Result {
+    let mut alg = (0..512)
+        .map(|_| if rng.below(2) == 0 { '#' } else { '.' })
+        .collect::<Vec<_>>();
+    // when the dark infinity lights up, it has to go dark again
+    if alg[0] == '#' {
+        alg[511] = '.';
+    }
+    writeln!(w, "{}\n", alg.into_iter().collect::<String>())?;
+    grid(w, size, size, || if rng.below(2) == 0 { '#' } else { '.' })
+}
+
+fn day21(rng: &mut Rng, w: &mut String) -> Result {
+    for player in 1..=2 {
+        writeln!(
+            w,
+            "Player {} starting position: {}",
+            player,
+            1 + rng.below(10)
+        )?;
+    }
+    Ok(())
+}
+
+fn day22(rng: &mut Rng, size: usize, w: &mut String) -> Result {
+    // like the real input, a few small cuboids for part 1 first
+    let small = (size / 20).max(1);
+    for i in 0..size {
+        let (min, len) = if i < small {
+            (-50, 50)
+        } else {
+            (-100_000, 50_000)
+        };
+        let mut range = || {
+            let from = rng.between(min, -min - len);
+            (from, from + rng.between(len / 10, len))
+        };
+        let (mut x, mut y, mut z) = (range(), range(), range());
+        // part 1 assumes that the big cuboids are away from the small ones
+        let near = |(from, to): (i32, i32)| from <= 50 && to >= -50;
+        while i >= small && near(x) && near(y) && near(z) {
+            (x, y, z) = (range(), range(), range());
+        }
+        let (x, y, z) = (
+            format!("{}..{}", x.0, x.1),
+            format!("{}..{}", y.0, y.1),
+            format!("{}..{}", z.0, z.1),
+        );
+        let switch = if i == 0 || rng.below(4) > 0 {
+            "on"
+        } else {
+            "off"
+        };
+        writeln!(w, "{} x={},y={},z={}", switch, x, y, z)?;
+    }
+    Ok(())
+}
+
+fn day23(rng: &mut Rng, w: &mut String) -> Result {
+    let mut pods = *b"AABBCCDD";
+    rng.shuffle(&mut pods);
+    let row = |pods: &[u8]| join(pods.iter().map(|p| *p as char), "#");
+    writeln!(w, "#############\n#...........#")?;
+    writeln!(w, "###{}###", row(&pods[..4]))?;
+    writeln!(w, "  #{}#", row(&pods[4..]))?;
+    writeln!(w, "  #########")
+}
+
+fn day24(rng: &mut Rng, w: &mut String) -> Result {
+    // 7 blocks push a digit on z, and 7 pop one and compare it with the
+    // current digit, in a random but balanced order
+    let mut pushes = vec![true; 7];
+    pushes.extend([false; 7]);
+    while {
+        rng.shuffle(&mut pushes);
+        let mut depth = 0i32;
+        pushes.iter().any(|&push| {
+            depth += if push { 1 } else { -1 };
+            depth < 0
+        })
+    } {}
+
+    let mut stack = vec![];
+    for push in pushes {
+        let (div, x, y) = if push {
+            let y = rng.between(1, 16);
+            stack.push(y);
+            (1, rng.between(10, 16), y)
+        } else {
+            // the digits differ by at most 8
+            let y_pushed = stack.pop().unwrap();
+            (26, rng.between(-8, 8) - y_pushed, rng.between(1, 16))
+        };
+        writeln!(w, "inp w\nmul x 0\nadd x z\nmod x 26\ndiv z {}", div)?;
+        writeln!(w, "add x {}\neql x w\neql x 0\nmul y 0\nadd y 25", x)?;
+        writeln!(w, "mul y x\nadd y 1\nmul z y\nmul y 0\nadd y w")?;
+        writeln!(w, "add y {}\nmul y x\nadd z y", y)?;
+    }
+    Ok(())
+}
+
+fn day25(rng: &mut Rng, size: usize, w: &mut String) -> Result {
+    // Herds with a free way around the sea floor never stop moving, and part
+    // 1 would never end. Random grids rarely have one, otherwise the retry
+    // happens without any empty cell, which settles right away.
+    for _ in 0..100 {
+        let mut raw = String::new();
+        grid(&mut raw, size, size, || *rng.choose(&['>', 'v', '.']))?;
+        let herds = day25::Day25::parse(&raw).expect("a valid grid");
+        if day25::steps_to_settle(&herds, 1000.max(10 * size)).is_some() {
+            return write!(w, "{}", raw);
+        }
+    }
+    grid(w, size, size, || *rng.choose(&['>', 'v']))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::DAYS;
+
+    #[test]
+    fn test_rng() {
+        let mut rng = Rng::new(0);
+        for _ in 0..1000 {
+            assert!((-3..=3).contains(&rng.between(-3, 3)));
+        }
+        let mut v = (0..10).collect::<Vec<_>>();
+        rng.shuffle(&mut v);
+        v.sort_unstable();
+        assert_eq!((0..10).collect::<Vec<_>>(), v);
+    }
+
+    #[test]
+    fn test_deterministic() {
+        assert_eq!(generate(15, 20, 3), generate(15, 20, 3));
+        assert_ne!(generate(15, 20, 3), generate(15, 20, 4));
+        assert_eq!(None, generate(26, 20, 3));
+    }
+
+    #[test]
+    fn test_every_day_solves() {
+        for day in DAYS {
+            for seed in 0..3 {
+                let raw = generate(day.day(), default_size(day.day()), seed).unwrap();
+                if let Err(err) = day.solve_str(&raw) {
+                    panic!("day {} with seed {}: {}\n{}", day.day(), seed, err, raw);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_extreme_sizes_solve() {
+        // tiny grids that never settle down, and the most caves
+        for (day, size) in [(9, 1), (11, 2), (12, 62), (25, 2), (25, 5)] {
+            let raw = generate(day, size, 0).unwrap();
+            let solution = crate::get_day(day).unwrap();
+            assert!(
+                solution.solve_str(&raw).is_ok(),
+                "day {} size {}",
+                day,
+                size
+            );
+        }
+    }
+}
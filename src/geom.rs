@@ -0,0 +1,355 @@
+use std::fmt::Display;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// Number usable as a coordinate.
+pub trait Coord:
+    Copy + Ord + Default + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn abs_diff(self, other: Self) -> Self {
+        if self > other {
+            self - other
+        } else {
+            other - self
+        }
+    }
+}
+
+macro_rules! impl_coord {
+    ($($t:ty),*) => {
+        $(impl Coord for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+        })*
+    };
+}
+
+impl_coord!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, PartialOrd, Ord)]
+pub struct Point2<T> {
+    pub x: T,
+    pub y: T,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, PartialOrd, Ord)]
+pub struct Point3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+impl<T> Point2<T> {
+    pub const fn new(x: T, y: T) -> Self {
+        Self { x, y }
+    }
+}
+
+impl<T> Point3<T> {
+    pub const fn new(x: T, y: T, z: T) -> Self {
+        Self { x, y, z }
+    }
+}
+
+impl<T: Coord> Point2<T> {
+    pub fn manhattan(&self, other: &Self) -> T {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+
+    /// largest distance along a single axis
+    pub fn chebyshev(&self, other: &Self) -> T {
+        self.x.abs_diff(other.x).max(self.y.abs_diff(other.y))
+    }
+}
+
+impl<T: Coord> Point3<T> {
+    pub fn manhattan(&self, other: &Self) -> T {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y) + self.z.abs_diff(other.z)
+    }
+
+    /// largest distance along a single axis
+    pub fn chebyshev(&self, other: &Self) -> T {
+        self.x
+            .abs_diff(other.x)
+            .max(self.y.abs_diff(other.y))
+            .max(self.z.abs_diff(other.z))
+    }
+}
+
+/// Component-wise operators, for values and references, and scaling.
+macro_rules! impl_ops {
+    ($point:ident { $($c:ident),* }) => {
+        impl<T: Add<Output = T>> Add for $point<T> {
+            type Output = Self;
+
+            fn add(self, rhs: Self) -> Self {
+                $point { $($c: self.$c + rhs.$c),* }
+            }
+        }
+
+        impl<T: Sub<Output = T>> Sub for $point<T> {
+            type Output = Self;
+
+            fn sub(self, rhs: Self) -> Self {
+                $point { $($c: self.$c - rhs.$c),* }
+            }
+        }
+
+        impl<T: Copy + Add<Output = T>> Add for &$point<T> {
+            type Output = $point<T>;
+
+            fn add(self, rhs: Self) -> $point<T> {
+                *self + *rhs
+            }
+        }
+
+        impl<T: Copy + Sub<Output = T>> Sub for &$point<T> {
+            type Output = $point<T>;
+
+            fn sub(self, rhs: Self) -> $point<T> {
+                *self - *rhs
+            }
+        }
+
+        impl<T: Copy + Add<Output = T>> AddAssign for $point<T> {
+            fn add_assign(&mut self, rhs: Self) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl<T: Copy + Sub<Output = T>> SubAssign for $point<T> {
+            fn sub_assign(&mut self, rhs: Self) {
+                *self = *self - rhs;
+            }
+        }
+
+        impl<T: Neg<Output = T>> Neg for $point<T> {
+            type Output = Self;
+
+            fn neg(self) -> Self {
+                $point { $($c: -self.$c),* }
+            }
+        }
+
+        impl<T: Copy + Mul<Output = T>> Mul<T> for $point<T> {
+            type Output = Self;
+
+            fn mul(self, rhs: T) -> Self {
+                $point { $($c: self.$c * rhs),* }
+            }
+        }
+    };
+}
+
+impl_ops!(Point2 { x, y });
+impl_ops!(Point3 { x, y, z });
+
+impl<T> From<(T, T)> for Point2<T> {
+    fn from((x, y): (T, T)) -> Self {
+        Self { x, y }
+    }
+}
+
+impl<T> From<(T, T, T)> for Point3<T> {
+    fn from((x, y, z): (T, T, T)) -> Self {
+        Self { x, y, z }
+    }
+}
+
+impl<T: Display> Display for Point2<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({},{})", self.x, self.y)
+    }
+}
+
+impl<T: Display> Display for Point3<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({},{},{})", self.x, self.y, self.z)
+    }
+}
+
+/// 3x3 integer matrix, mostly used for rotations, indexed by row then
+/// column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct M3(pub [[i32; 3]; 3]);
+
+impl M3 {
+    pub const IDENTITY: M3 = M3([[1, 0, 0], [0, 1, 0], [0, 0, 1]]);
+
+    pub fn transpose(&self) -> Self {
+        let m = &self.0;
+        M3([
+            [m[0][0], m[1][0], m[2][0]],
+            [m[0][1], m[1][1], m[2][1]],
+            [m[0][2], m[1][2], m[2][2]],
+        ])
+    }
+
+    /// only valid for rotations, whose inverse is their transpose
+    pub fn inv(&self) -> Self {
+        self.transpose()
+    }
+
+    /// the 24 rotations keeping a cube aligned with the axes
+    pub fn rotations() -> impl Iterator<Item = M3> {
+        let rots = [
+            [[1, 0, 0], [0, 1, 0], [0, 0, 1]],
+            [[1, 0, 0], [0, 0, -1], [0, 1, 0]],
+            [[1, 0, 0], [0, -1, 0], [0, 0, -1]],
+            [[1, 0, 0], [0, 0, 1], [0, -1, 0]],
+            [[-1, 0, 0], [0, 1, 0], [0, 0, -1]],
+            [[-1, 0, 0], [0, 0, -1], [0, -1, 0]],
+            [[-1, 0, 0], [0, -1, 0], [0, 0, 1]],
+            [[-1, 0, 0], [0, 0, 1], [0, 1, 0]],
+        ];
+
+        let perms = [
+            [[1, 0, 0], [0, 1, 0], [0, 0, 1]],  // x, y, z
+            [[0, 1, 0], [0, 0, 1], [1, 0, 0]],  // y, z, x
+            [[0, 0, 1], [0, 1, 0], [-1, 0, 0]], // z, y, -x
+        ];
+
+        rots.into_iter()
+            .flat_map(move |a| perms.into_iter().map(move |b| M3(a) * M3(b)))
+    }
+}
+
+impl Mul for M3 {
+    type Output = M3;
+
+    fn mul(self, rhs: M3) -> M3 {
+        let (a, b) = (&self.0, &rhs.0);
+        let cell = |i: usize, j: usize| (0..3).map(|k| a[i][k] * b[k][j]).sum();
+        M3([
+            [cell(0, 0), cell(0, 1), cell(0, 2)],
+            [cell(1, 0), cell(1, 1), cell(1, 2)],
+            [cell(2, 0), cell(2, 1), cell(2, 2)],
+        ])
+    }
+}
+
+impl Mul<Point3<i32>> for M3 {
+    type Output = Point3<i32>;
+
+    fn mul(self, p: Point3<i32>) -> Point3<i32> {
+        let m = &self.0;
+        Point3 {
+            x: p.x * m[0][0] + p.y * m[0][1] + p.z * m[0][2],
+            y: p.x * m[1][0] + p.y * m[1][1] + p.z * m[1][2],
+            z: p.x * m[2][0] + p.y * m[2][1] + p.z * m[2][2],
+        }
+    }
+}
+
+/// Axis aligned box, both corners included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Aabb<T> {
+    pub min: Point3<T>,
+    pub max: Point3<T>,
+}
+
+impl<T: Coord> Aabb<T> {
+    pub fn new(min: Point3<T>, max: Point3<T>) -> Self {
+        Self { min, max }
+    }
+
+    pub fn contains(&self, p: &Point3<T>) -> bool {
+        (self.min.x..=self.max.x).contains(&p.x)
+            && (self.min.y..=self.max.y).contains(&p.y)
+            && (self.min.z..=self.max.z).contains(&p.z)
+    }
+
+    /// rejects disjoint boxes before building anything, most of the boxes
+    /// day 22 intersects don't overlap
+    pub fn intersect(&self, other: &Self) -> Option<Self> {
+        if self.min.x > other.max.x
+            || self.max.x < other.min.x
+            || self.min.y > other.max.y
+            || self.max.y < other.min.y
+            || self.min.z > other.max.z
+            || self.max.z < other.min.z
+        {
+            return None;
+        }
+        Some(Self {
+            min: Point3 {
+                x: self.min.x.max(other.min.x),
+                y: self.min.y.max(other.min.y),
+                z: self.min.z.max(other.min.z),
+            },
+            max: Point3 {
+                x: self.max.x.min(other.max.x),
+                y: self.max.y.min(other.max.y),
+                z: self.max.z.min(other.max.z),
+            },
+        })
+    }
+
+    /// number of integer points inside
+    pub fn volume(&self) -> T {
+        (self.max.x - self.min.x + T::ONE)
+            * (self.max.y - self.min.y + T::ONE)
+            * (self.max.z - self.min.z + T::ONE)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_ops() {
+        let a = Point2::new(1, 2);
+        let b = Point2::new(4, -2);
+        assert_eq!(Point2::new(5, 0), a + b);
+        assert_eq!(Point2::new(-3, 4), a - b);
+        assert_eq!(Point2::new(-2, -4), -a * 2);
+        let mut c = Point3::new(1, 2, 3);
+        c += Point3::new(1, 1, 1);
+        c -= Point3::new(0, 0, 4);
+        assert_eq!(Point3::from((2, 3, 0)), c);
+        assert_eq!("(2,3,0)", c.to_string());
+    }
+
+    #[test]
+    fn test_distances() {
+        let a = Point3::new(1105, -1205, 1229);
+        let b = Point3::new(-92, -2380, -20);
+        assert_eq!(3621, a.manhattan(&b));
+        assert_eq!(1249, a.chebyshev(&b));
+        let (a, b) = (Point2::new(0u32, 5), Point2::new(3, 1));
+        assert_eq!((7, 4), (a.manhattan(&b), a.chebyshev(&b)));
+    }
+
+    #[test]
+    fn test_rotations() {
+        let rotations = M3::rotations().collect::<Vec<_>>();
+        assert_eq!(24, rotations.len());
+        let p = Point3::new(1, 2, 3);
+        assert_eq!(p, M3::IDENTITY * p);
+        let mut images = rotations.iter().map(|m| *m * p).collect::<Vec<_>>();
+        images.sort();
+        images.dedup();
+        assert_eq!(24, images.len());
+        for m in rotations {
+            assert_eq!(M3::IDENTITY, m * m.inv());
+        }
+
+        let m = M3([[0, -1, 0], [1, 0, 0], [0, 0, -1]]);
+        assert_eq!(Point3::new(-20, 10, -30), m * Point3::new(10, 20, 30));
+    }
+
+    #[test]
+    fn test_aabb() {
+        let a = Aabb::new(Point3::new(10, 10, 10), Point3::new(12, 12, 12));
+        let b = Aabb::new(Point3::new(11, 11, 11), Point3::new(13, 13, 13));
+        assert_eq!(27, a.volume());
+        assert_eq!(Some(8), a.intersect(&b).map(|i| i.volume()));
+        assert!(a.contains(&Point3::new(12, 10, 11)));
+        assert!(!a.contains(&Point3::new(13, 10, 11)));
+        let far = Aabb::new(Point3::new(20, 0, 0), Point3::new(30, 12, 12));
+        assert_eq!(None, a.intersect(&far));
+    }
+}
@@ -0,0 +1,130 @@
+use std::borrow::Cow;
+use std::io::Read;
+use std::path::PathBuf;
+
+use crate::error::Error;
+use crate::DynSolution;
+
+/// Where to read the puzzle input of a day from.
+#[derive(Debug, Clone, Default)]
+pub enum InputSource {
+    /// the input embedded in the binary
+    #[default]
+    Embedded,
+    /// a single file, only meaningful when running one day
+    File(PathBuf),
+    Stdin,
+    /// a directory containing one `dayNN.txt` file per day
+    Dir(PathBuf),
+}
+
+impl InputSource {
+    /// `-` stands for stdin, anything else is a path.
+    pub fn from_arg(arg: &str) -> Self {
+        if arg == "-" {
+            InputSource::Stdin
+        } else {
+            InputSource::File(arg.into())
+        }
+    }
+
+    /// whether this source can provide the input for several days
+    pub fn is_shared(&self) -> bool {
+        matches!(self, InputSource::Embedded | InputSource::Dir(_))
+    }
+
+    pub fn load(&self, day: &dyn DynSolution) -> Result<Cow<'static, str>, Error> {
+        match self {
+            InputSource::Embedded => Ok(Cow::Borrowed(day.input())),
+            InputSource::File(path) => {
+                std::fs::read_to_string(path)
+                    .map(Cow::Owned)
+                    .map_err(|error| Error::Io {
+                        path: path.display().to_string(),
+                        error,
+                    })
+            }
+            InputSource::Stdin => {
+                let mut raw = String::new();
+                std::io::stdin()
+                    .read_to_string(&mut raw)
+                    .map_err(|error| Error::Io {
+                        path: "stdin".to_string(),
+                        error,
+                    })?;
+                Ok(Cow::Owned(raw))
+            }
+            InputSource::Dir(dir) => {
+                InputSource::File(dir.join(format!("{}.txt", day.name()))).load(day)
+            }
+        }
+    }
+}
+
+/// Input as every parser expects it, whatever the machine it was saved on:
+/// without byte order mark, with `\n` line endings, without trailing
+/// whitespace on each line, and ending with a single newline. Only
+/// allocates when something has to change.
+pub fn normalize(raw: &str) -> Cow<'_, str> {
+    let raw = raw.strip_prefix('\u{feff}').unwrap_or(raw);
+    let body = raw.trim_end();
+    if body.is_empty() {
+        return Cow::Borrowed("");
+    }
+    let clean = raw.len() == body.len() + 1
+        && raw.ends_with('\n')
+        && !body.contains('\r')
+        && body.lines().all(|line| line.len() == line.trim_end().len());
+    if clean {
+        return Cow::Borrowed(raw);
+    }
+
+    let mut normalized = String::with_capacity(body.len() + 1);
+    for line in body.lines() {
+        normalized.push_str(line.trim_end());
+        normalized.push('\n');
+    }
+    Cow::Owned(normalized)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert!(matches!(normalize("1\n 2\n\n3\n"), Cow::Borrowed(_)));
+        assert_eq!("1\n 2\n\n3\n", normalize("\u{feff}1 \r\n 2\r\n\r\n3\t"));
+        assert_eq!("1\n2\n", normalize("1\n2\n\n  \n"));
+        assert_eq!("1\n", normalize("1"));
+        assert_eq!("", normalize("\u{feff}\r\n"));
+        for day in crate::DAYS {
+            assert_eq!(day.input(), normalize(day.input()), "{}", day.name());
+        }
+    }
+
+    #[test]
+    fn test_load_dir() {
+        let day = crate::get_day(1).unwrap();
+        let dir = std::env::temp_dir().join(format!("aoc2021-inputs-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("day01.txt"), "1\n2\n3\n4\n").unwrap();
+
+        let source = InputSource::Dir(dir.clone());
+        let raw = source.load(day).unwrap();
+        assert_eq!("1\n2\n3\n4\n", raw);
+        assert_eq!(
+            ("3".to_string(), "1".to_string()),
+            day.solve_str(&raw).unwrap()
+        );
+        assert!(source.load(crate::get_day(2).unwrap()).is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_embedded() {
+        let day = crate::get_day(3).unwrap();
+        assert_eq!(day.input(), InputSource::Embedded.load(day).unwrap());
+    }
+}
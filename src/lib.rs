@@ -1,3 +1,18 @@
+pub mod allocs;
+pub mod answers;
+pub mod budget;
+pub mod error;
+pub mod gen;
+pub mod geom;
+pub mod input;
+pub mod output;
+#[cfg(test)]
+mod prop;
+pub mod render;
+pub mod runner;
+pub mod search;
+pub mod solution;
+pub mod trace;
 pub mod utils;
 
 pub mod day01;
@@ -25,3 +40,58 @@ pub mod day22;
 pub mod day23;
 pub mod day24;
 pub mod day25;
+
+pub use error::{Error, ParseError};
+pub use solution::{DynSolution, Solution};
+
+/// Every implemented day, in calendar order.
+pub static DAYS: [&dyn DynSolution; 25] = [
+    &day01::Day01,
+    &day02::Day02,
+    &day03::Day03,
+    &day04::Day04,
+    &day05::Day05,
+    &day06::Day06,
+    &day07::Day07,
+    &day08::Day08,
+    &day09::Day09,
+    &day10::Day10,
+    &day11::Day11,
+    &day12::Day12,
+    &day13::Day13,
+    &day14::Day14,
+    &day15::Day15,
+    &day16::Day16,
+    &day17::Day17,
+    &day18::Day18,
+    &day19::Day19,
+    &day20::Day20,
+    &day21::Day21,
+    &day22::Day22,
+    &day23::Day23,
+    &day24::Day24,
+    &day25::Day25,
+];
+
+pub fn get_day(day: u8) -> Option<&'static dyn DynSolution> {
+    DAYS.iter().find(|d| d.day() == day).copied()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_registry_order() {
+        for (i, d) in DAYS.iter().enumerate() {
+            assert_eq!(i + 1, d.day() as usize, "{}", d.name());
+        }
+    }
+
+    #[test]
+    fn test_get_day() {
+        assert_eq!(Some(12), get_day(12).map(|d| d.day()));
+        assert!(get_day(0).is_none());
+        assert!(get_day(26).is_none());
+    }
+}
@@ -1,103 +1,651 @@
 use std::env;
+use std::fs::File;
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
 
-use aoc2021::day01;
-use aoc2021::day02;
-use aoc2021::day03;
-use aoc2021::day04;
-use aoc2021::day05;
-use aoc2021::day06;
-use aoc2021::day07;
-use aoc2021::day08;
-use aoc2021::day09;
-use aoc2021::day10;
-use aoc2021::day11;
-use aoc2021::day12;
-use aoc2021::day13;
-use aoc2021::day14;
-use aoc2021::day15;
-use aoc2021::day16;
-use aoc2021::day17;
-use aoc2021::day18;
-use aoc2021::day19;
-use aoc2021::day20;
-use aoc2021::day21;
-use aoc2021::day22;
-use aoc2021::day23;
-use aoc2021::day24;
-use aoc2021::day25;
+use itertools::Itertools;
+
+use aoc2021::answers::{self, Answers};
+use aoc2021::budget::{self, Budget, Row};
+use aoc2021::day04::{self, Bingo};
+use aoc2021::input::InputSource;
+use aoc2021::output::{Format, Printer};
+use aoc2021::render::{self, Frames};
+use aoc2021::runner::{self, fmt_duration, Report};
+use aoc2021::trace;
+use aoc2021::{DynSolution, Error};
+
+const USAGE: &str = "usage: aoc2021 [DAY] [--input PATH|-] [--inputs-dir DIR] [--repeat N]
+               [--format text|json|csv|tap] [--parallel[=N]]
+               [--trace] [--trace-steps N] [--trace-file PATH] [--trace-frames DIR]
+       aoc2021 verify [DAY] [--answers PATH] [--input PATH|-] [--inputs-dir DIR]
+               [--parallel[=N]]
+       aoc2021 --check-budget [DAY] [--budget PATH] [--baseline PATH] [--repeat N]
+       aoc2021 gen DAY [--size N] [--seed N]
+       aoc2021 check DAY [PATH|-]
+       aoc2021 render DAY [--input PATH|-] [--image-format ppm|pgm|svg] [--scale N]
+               [--output PATH]
+       aoc2021 bingo [--board N] [--diagonals] [--input PATH|-]
+
+Run all days, or only DAY. Without any input option, the puzzle inputs
+embedded in the binary are used.
+With verify, the answers are compared with the expected ones, and the exit
+code is 1 if any of them differ.
+With --check-budget, the median duration of every day is compared with its
+budget, and the exit code is 1 if any of them is too slow.
+With check, the input of DAY, read from PATH if given, is only validated
+without being solved. Every problem found is reported, and the exit code is
+1 if there is any.
+With render, a picture of the puzzle of DAY is saved to dayNN.ppm, or the
+path given with --output. Only some days can be rendered.
+With bingo, the boards of day 4 that can never win with the drawn numbers
+are listed. With --board, the fewest draws needed by board N to win are
+printed too, followed by a reordering of the drawn numbers achieving it.
+With gen, a random input for DAY is printed, always the same for a given
+size and seed.
+
+    --input PATH      read the input of DAY from PATH, or stdin if PATH is -
+    --inputs-dir DIR  read the input of each day from DIR/dayNN.txt
+    --repeat N        run every day N times and report the min and median
+                      duration of each phase, 5 by default with --check-budget
+    --format FORMAT   print the results as text (default), json, csv or tap
+    --answers PATH    expected answers, in the format of answers.toml, which
+                      is used by default. With --format tap, a part whose
+                      answer differs is not ok, the default answers only
+                      being used for the embedded inputs
+    --parallel[=N]    run the days, and the two parts of each day, on N
+                      threads, one per core by default
+    --budget PATH     time budget, in the format of budget.toml, which is
+                      used by default
+    --baseline PATH   also fail when slower than the output of a previous
+                      run with --format json, by more than the tolerance set
+                      in the budget
+    --size N          size of the generated input, whose meaning depends on
+                      the day, close to the real inputs by default
+    --seed N          seed of the generated input, 0 by default
+    --trace           print every intermediate state of the simulations
+                      (days 6, 11, 20, 23 and 25) to stderr
+    --trace-steps N   only trace the first N steps of each part
+    --trace-file PATH write the trace to PATH instead of stderr
+    --trace-frames DIR
+                      save the traced states as numbered images in DIR,
+                      like DIR/day11-part1-0042.ppm
+    --image-format FORMAT
+                      format of the images, ppm (default), pgm or svg
+    --scale N         draw every cell of the images as N x N pixels, 4 by
+                      default
+    --output PATH     where render saves the picture
+    --board N         the board of bingo to make win, from 0
+    --diagonals       let the bingo boards also win with their diagonals";
+
+#[derive(Debug, Default)]
+struct Options {
+    day: Option<u8>,
+    source: InputSource,
+    repeat: Option<usize>,
+    format: Format,
+    verify: bool,
+    answers: Option<PathBuf>,
+    check_budget: bool,
+    budget: Option<PathBuf>,
+    baseline: Option<PathBuf>,
+    /// number of threads
+    parallel: Option<usize>,
+    gen: bool,
+    check: bool,
+    trace: bool,
+    trace_steps: Option<usize>,
+    trace_file: Option<PathBuf>,
+    trace_frames: Option<PathBuf>,
+    render: bool,
+    image_format: render::Format,
+    scale: Option<usize>,
+    output: Option<PathBuf>,
+    bingo: bool,
+    board: Option<usize>,
+    diagonals: bool,
+    size: Option<usize>,
+    seed: Option<u64>,
+}
+
+impl Options {
+    fn is_traced(&self) -> bool {
+        self.trace || self.trace_frames.is_some()
+    }
+
+    fn scale(&self) -> usize {
+        self.scale.unwrap_or(4)
+    }
+
+    fn repeat(&self) -> usize {
+        match self.repeat {
+            Some(n) => n,
+            None if self.check_budget => 5,
+            None => 1,
+        }
+    }
+}
 
 fn main() {
-    let arg = env::args().nth(1).and_then(|s| s.parse::<u8>().ok());
+    let opts = match parse_args(env::args().skip(1)) {
+        Ok(opts) => opts,
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, USAGE);
+            std::process::exit(2)
+        }
+    };
 
-    match arg {
-        Some(x) => match run_day(x) {
-            Ok(_) => (),
-            Err(err) => {
-                eprintln!("{}", err);
-                std::process::exit(1)
+    match run(&opts) {
+        Ok(true) => (),
+        Ok(false) => std::process::exit(1),
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1)
+        }
+    }
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut opts = Options::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" => {
+                let path = args.next().ok_or("--input requires a path")?;
+                opts.source = InputSource::from_arg(&path);
+            }
+            "--inputs-dir" => {
+                let dir = args.next().ok_or("--inputs-dir requires a directory")?;
+                opts.source = InputSource::Dir(dir.into());
+            }
+            "--repeat" => {
+                let repeat = args
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .filter(|n| *n > 0)
+                    .ok_or("--repeat requires a positive number")?;
+                opts.repeat = Some(repeat);
+            }
+            "--format" => {
+                let format = args.next().ok_or("--format requires a format")?;
+                opts.format = format.parse()?;
+            }
+            "--parallel" => {
+                let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+                opts.parallel = Some(cores);
+            }
+            _ if arg.starts_with("--parallel=") => {
+                let threads = arg["--parallel=".len()..]
+                    .parse()
+                    .ok()
+                    .filter(|n| *n > 0)
+                    .ok_or("--parallel=N requires a positive number")?;
+                opts.parallel = Some(threads);
+            }
+            "verify" => opts.verify = true,
+            "--answers" => {
+                let path = args.next().ok_or("--answers requires a path")?;
+                opts.answers = Some(path.into());
+            }
+            "--check-budget" => opts.check_budget = true,
+            "--budget" => {
+                let path = args.next().ok_or("--budget requires a path")?;
+                opts.budget = Some(path.into());
+            }
+            "--baseline" => {
+                let path = args.next().ok_or("--baseline requires a path")?;
+                opts.baseline = Some(path.into());
+            }
+            "gen" => opts.gen = true,
+            "check" => opts.check = true,
+            "--size" => {
+                let size = args
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .filter(|n| *n > 0)
+                    .ok_or("--size requires a positive number")?;
+                opts.size = Some(size);
+            }
+            "--seed" => {
+                let seed = args
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .ok_or("--seed requires a number")?;
+                opts.seed = Some(seed);
+            }
+            "--trace" => opts.trace = true,
+            "--trace-steps" => {
+                let steps = args
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .ok_or("--trace-steps requires a number")?;
+                opts.trace_steps = Some(steps);
+            }
+            "--trace-file" => {
+                let path = args.next().ok_or("--trace-file requires a path")?;
+                opts.trace_file = Some(path.into());
+            }
+            "--trace-frames" => {
+                let dir = args.next().ok_or("--trace-frames requires a directory")?;
+                opts.trace_frames = Some(dir.into());
+            }
+            "render" => opts.render = true,
+            "--image-format" => {
+                let format = args.next().ok_or("--image-format requires a format")?;
+                opts.image_format = format.parse()?;
             }
-        },
+            "--scale" => {
+                let scale = args
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .filter(|n| *n > 0)
+                    .ok_or("--scale requires a positive number")?;
+                opts.scale = Some(scale);
+            }
+            "--output" => {
+                let path = args.next().ok_or("--output requires a path")?;
+                opts.output = Some(path.into());
+            }
+            "bingo" => opts.bingo = true,
+            "--board" => {
+                let board = args
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .ok_or("--board requires a number")?;
+                opts.board = Some(board);
+            }
+            "--diagonals" => opts.diagonals = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                std::process::exit(0)
+            }
+            // the file to check comes after the day
+            _ if opts.check && opts.day.is_some() => opts.source = InputSource::from_arg(&arg),
+            _ => {
+                let day = arg
+                    .parse()
+                    .map_err(|_| format!("invalid argument {}", arg))?;
+                opts.day = Some(day);
+            }
+        }
+    }
+
+    if opts.bingo {
+        match opts.day {
+            None | Some(4) => opts.day = Some(4),
+            Some(_) => return Err("bingo only plays the boards of day 4".to_string()),
+        }
+    }
+    if opts.day.is_none() && !opts.source.is_shared() {
+        return Err("--input can only be used when running a single day".to_string());
+    }
+    if opts.answers.is_some() && !opts.verify && opts.format != Format::Tap {
+        return Err("--answers can only be used with verify or --format tap".to_string());
+    }
+    if (opts.budget.is_some() || opts.baseline.is_some()) && !opts.check_budget {
+        return Err("--budget and --baseline can only be used with --check-budget".to_string());
+    }
+    if opts.verify && opts.check_budget {
+        return Err("verify and --check-budget cannot be used together".to_string());
+    }
+    if (opts.size.is_some() || opts.seed.is_some()) && !opts.gen {
+        return Err("--size and --seed can only be used with gen".to_string());
+    }
+    if opts.gen && (opts.verify || opts.check_budget) {
+        return Err("gen cannot be used with verify or --check-budget".to_string());
+    }
+    if opts.gen && opts.day.is_none() {
+        return Err("gen requires a day".to_string());
+    }
+    if opts.check && (opts.verify || opts.check_budget || opts.gen) {
+        return Err("check cannot be used with verify, --check-budget or gen".to_string());
+    }
+    if opts.check && opts.day.is_none() {
+        return Err("check requires a day".to_string());
+    }
+    if opts.trace_file.is_some() && !opts.trace {
+        return Err("--trace-file can only be used with --trace".to_string());
+    }
+    if opts.trace_steps.is_some() && !opts.is_traced() {
+        return Err("--trace-steps can only be used with --trace or --trace-frames".to_string());
+    }
+    // the trace is only collected on the main thread
+    if opts.is_traced()
+        && (opts.parallel.is_some() || opts.check_budget || opts.gen || opts.check || opts.render)
+    {
+        return Err(
+            "--trace and --trace-frames cannot be used with --parallel, --check-budget, gen, \
+             check or render"
+                .to_string(),
+        );
+    }
+    if opts.render && (opts.verify || opts.check_budget || opts.gen || opts.check) {
+        return Err("render cannot be used with verify, --check-budget, gen or check".to_string());
+    }
+    if opts.render && opts.day.is_none() {
+        return Err("render requires a day".to_string());
+    }
+    if opts.output.is_some() && !opts.render {
+        return Err("--output can only be used with render".to_string());
+    }
+    if opts.bingo && (opts.verify || opts.check_budget || opts.gen || opts.check || opts.render) {
+        return Err(
+            "bingo cannot be used with verify, --check-budget, gen, check or render".to_string(),
+        );
+    }
+    if (opts.board.is_some() || opts.diagonals) && !opts.bingo {
+        return Err("--board and --diagonals can only be used with bingo".to_string());
+    }
+    Ok(opts)
+}
+
+/// returns false when some answers are wrong in verify mode or in the TAP
+/// output
+fn run(opts: &Options) -> Result<bool, Error> {
+    if !opts.is_traced() {
+        return run_traced(opts);
+    }
+    let (out, path): (Option<Box<dyn io::Write>>, _) = match &opts.trace_file {
+        _ if !opts.trace => (None, "trace".to_string()),
+        Some(path) => {
+            let path = path.display().to_string();
+            let file = File::create(&path).map_err(|error| Error::Io {
+                path: path.clone(),
+                error,
+            })?;
+            (Some(Box::new(io::BufWriter::new(file))), path)
+        }
+        None => (Some(Box::new(io::stderr())), "stderr".to_string()),
+    };
+    let frames = match &opts.trace_frames {
+        Some(dir) => {
+            let frames = Frames::new(dir, opts.image_format, opts.scale());
+            Some(frames.map_err(|error| Error::Io {
+                path: dir.display().to_string(),
+                error,
+            })?)
+        }
+        None => None,
+    };
+    trace::start(out, frames, opts.trace_steps);
+    let result = run_traced(opts);
+    trace::stop().map_err(|error| Error::Io { path, error })?;
+    result
+}
+
+/// `run`, once the trace is set up, if any
+fn run_traced(opts: &Options) -> Result<bool, Error> {
+    let days = match opts.day {
+        Some(day) => vec![aoc2021::get_day(day).ok_or(Error::UnknownDay(day))?],
+        None => aoc2021::DAYS.to_vec(),
+    };
+    if opts.gen {
+        return gen(opts, days[0]);
+    }
+    if opts.check {
+        return check(opts, days[0]);
+    }
+    if opts.render {
+        return render(opts, days[0]);
+    }
+    if opts.bingo {
+        return bingo(opts, days[0]);
+    }
+    if opts.verify {
+        return verify(opts, &days);
+    }
+    if opts.check_budget {
+        return check_budget(opts, &days);
+    }
+
+    let stdout = io::stdout();
+    let mut printer = Printer::new(opts.format, stdout.lock());
+    if opts.format == Format::Tap {
+        if let Some(answers) = tap_answers(opts)? {
+            printer = printer.with_answers(answers);
+        }
+    }
+    printer.start(days.len()).map_err(stdout_error)?;
+    let times = run_days(opts, &days, |report| {
+        printer.report(&report).map_err(stdout_error)
+    });
+    if let Err(error) = &times {
+        printer.fail(error).map_err(stdout_error)?;
+    }
+    printer.finish().map_err(stdout_error)?;
+    let ok = printer.all_ok();
+    drop(printer);
+    let times = times?;
+
+    if opts.format == Format::Text {
+        print_times(times);
+    }
+    Ok(ok)
+}
+
+/// Call `f` with the report of each day, in order. In parallel mode, returns
+/// the wall and cpu times of the whole run.
+fn run_days(
+    opts: &Options,
+    days: &[&dyn DynSolution],
+    mut f: impl FnMut(Report) -> Result<(), Error>,
+) -> Result<Option<(Duration, Duration)>, Error> {
+    let threads = match opts.parallel {
+        Some(threads) => threads,
         None => {
-            let mut total = std::time::Duration::default();
-            for i in 1..=25 {
-                let start = std::time::Instant::now();
-                run_day(i).unwrap();
-                let elapsed = start.elapsed();
-                total += elapsed;
-                // println!(
-                //     r#"{{"day": {}, "total_ms": {}, "delta_ms": {}}},"#,
-                //     i,
-                //     total.as_millis(),
-                //     elapsed.as_millis(),
-                // );
-                println!(
-                    "total so far: {}ms (+{}ms)",
-                    total.as_millis(),
-                    elapsed.as_millis()
-                );
-            }
-            println!("total time: {}ms", total.as_millis());
-        }
-    }
-}
-
-fn run_day(day: u8) -> Result<(), String> {
-    match day {
-        1 => print_day("day01", day01::solve()),
-        2 => print_day("day02", day02::solve()),
-        3 => print_day("day03", day03::solve()),
-        4 => print_day("day04", day04::solve()),
-        5 => print_day("day05", day05::solve()),
-        6 => print_day("day06", day06::solve()),
-        7 => print_day("day07", day07::solve()),
-        8 => print_day("day08", day08::solve()),
-        9 => print_day("day09", day09::solve()),
-        10 => print_day("day10", day10::solve()),
-        11 => print_day("day11", day11::solve()),
-        12 => print_day("day12", day12::solve()),
-        13 => print_day("day13", day13::solve()),
-        14 => print_day("day14", day14::solve()),
-        15 => print_day("day15", day15::solve()),
-        16 => print_day("day16", day16::solve()),
-        17 => print_day("day17", day17::solve()),
-        18 => print_day("day18", day18::solve()),
-        19 => print_day("day19", day19::solve()),
-        20 => print_day("day20", day20::solve()),
-        21 => print_day("day21", day21::solve()),
-        22 => print_day("day22", day22::solve()),
-        23 => print_day("day23", day23::solve()),
-        24 => print_day("day24", day24::solve()),
-        25 => print_day("day25", day25::solve()),
-        _ => return Err(format!("invalid day {}", day)),
-    }
-    Ok(())
-}
-
-fn print_day<D1, D2>(tag: &str, results: (D1, D2))
-where
-    D1: std::fmt::Display,
-    D2: std::fmt::Display,
-{
-    let (r1, r2) = results;
-    println!("{} part 1: {}\n{} part 2: {}", tag, r1, tag, r2);
+            for day in days {
+                let raw = opts.source.load(*day)?;
+                f(runner::run_day(*day, &raw, opts.repeat())?)?;
+            }
+            return Ok(None);
+        }
+    };
+
+    let raws = days
+        .iter()
+        .map(|day| opts.source.load(*day))
+        .collect::<Result<Vec<_>, _>>()?;
+    let jobs = days
+        .iter()
+        .zip(&raws)
+        .map(|(day, raw)| (*day, raw.as_ref()))
+        .collect::<Vec<_>>();
+    let run = runner::run_parallel(&jobs, opts.repeat(), threads);
+    for report in run.reports {
+        f(report?)?;
+    }
+    Ok(Some((run.wall, run.cpu)))
+}
+
+fn print_times(times: Option<(Duration, Duration)>) {
+    if let Some((wall, cpu)) = times {
+        println!(
+            "wall time: {}, cpu time: {} ({:.1}x)",
+            fmt_duration(wall),
+            fmt_duration(cpu),
+            cpu.as_secs_f64() / wall.as_secs_f64()
+        );
+    }
+}
+
+fn gen(opts: &Options, day: &dyn DynSolution) -> Result<bool, Error> {
+    let size = opts
+        .size
+        .unwrap_or_else(|| aoc2021::gen::default_size(day.day()));
+    let raw = aoc2021::gen::generate(day.day(), size, opts.seed.unwrap_or(0))
+        .ok_or(Error::UnknownDay(day.day()))?;
+    print!("{}", raw);
+    Ok(true)
+}
+
+/// returns false when the input has any problem
+fn check(opts: &Options, day: &dyn DynSolution) -> Result<bool, Error> {
+    let raw = opts.source.load(day)?;
+    let errors = day.check(&raw);
+    for error in &errors {
+        let error = Error::Parse {
+            day: day.day(),
+            error: error.clone(),
+        };
+        println!("{}", error);
+    }
+    match errors.len() {
+        0 => println!("{}: ok", day.name()),
+        1 => println!("1 problem"),
+        n => println!("{} problems", n),
+    }
+    Ok(errors.is_empty())
+}
+
+fn render(opts: &Options, day: &dyn DynSolution) -> Result<bool, Error> {
+    let raw = opts.source.load(day)?;
+    let input = day.parse(&raw)?;
+    let image = match day.render(input.as_ref()) {
+        Some(image) => image,
+        None => {
+            eprintln!("{} cannot be rendered", day.name());
+            return Ok(false);
+        }
+    };
+    let path = match &opts.output {
+        Some(path) => path.clone(),
+        None => format!("{}.{}", day.name(), opts.image_format.extension()).into(),
+    };
+    render::save(&path, &image, opts.image_format, opts.scale()).map_err(|error| Error::Io {
+        path: path.display().to_string(),
+        error,
+    })?;
+    println!("{}", path.display());
+    Ok(true)
+}
+
+/// returns false when the board asked for can never win
+fn bingo(opts: &Options, day: &dyn DynSolution) -> Result<bool, Error> {
+    let raw = opts.source.load(day)?;
+    let input = day.parse(&raw)?;
+    let puzzle = input
+        .downcast_ref::<day04::Puzzle>()
+        .expect("the input of day 4");
+    if let Some(board) = opts.board.filter(|b| *b >= puzzle.grids.len()) {
+        eprintln!("there is no board {}", board);
+        return Ok(false);
+    }
+    let bingo = Bingo::new(&puzzle.grids).diagonals(opts.diagonals);
+
+    let never = bingo.never_winning(&puzzle.numbers);
+    println!(
+        "{} of {} boards can never win{}",
+        never.len(),
+        puzzle.grids.len(),
+        if never.is_empty() { "" } else { ":" }
+    );
+    if !never.is_empty() {
+        println!("{}", never.iter().join(","));
+    }
+
+    let board = match opts.board {
+        Some(board) => board,
+        None => return Ok(true),
+    };
+    match bingo.fastest_win(board, &puzzle.numbers) {
+        Some(strategy) => {
+            println!(
+                "board {} can win after {} draws with:",
+                board, strategy.draws
+            );
+            println!("{}", strategy.order.iter().join(","));
+            Ok(true)
+        }
+        None => {
+            println!("board {} can never win", board);
+            Ok(false)
+        }
+    }
+}
+
+fn read_answers(opts: &Options) -> Result<Answers, Error> {
+    read_data(opts.answers.as_ref(), "answers.toml", answers::EMBEDDED)
+        .and_then(|(path, raw)| Answers::parse(&raw).map_err(|error| Error::File { path, error }))
+}
+
+/// the answers checked by the TAP output: the ones given with --answers, or
+/// the embedded ones when solving the embedded inputs
+fn tap_answers(opts: &Options) -> Result<Option<Answers>, Error> {
+    if opts.answers.is_none() && !matches!(opts.source, InputSource::Embedded) {
+        return Ok(None);
+    }
+    read_answers(opts).map(Some)
+}
+
+fn verify(opts: &Options, days: &[&dyn DynSolution]) -> Result<bool, Error> {
+    let answers = read_answers(opts)?;
+
+    let mut mismatches = 0;
+    let times = run_days(opts, days, |report| {
+        let wrong = answers.check(&report);
+        if wrong.is_empty() {
+            println!("day{:02}: ok", report.day);
+        }
+        for mismatch in &wrong {
+            println!("{}", mismatch);
+        }
+        mismatches += wrong.len();
+        Ok(())
+    })?;
+
+    let total = days.len() * 2;
+    println!("{}/{} answers match", total - mismatches, total);
+    print_times(times);
+    Ok(mismatches == 0)
+}
+
+fn check_budget(opts: &Options, days: &[&dyn DynSolution]) -> Result<bool, Error> {
+    let budget = read_data(opts.budget.as_ref(), "budget.toml", budget::EMBEDDED)
+        .and_then(|(path, raw)| Budget::parse(&raw).map_err(|error| Error::File { path, error }))?;
+    let baseline = match &opts.baseline {
+        Some(path) => {
+            let (path, raw) = read_data(Some(path), "", "")?;
+            let baseline =
+                budget::parse_baseline(&raw).map_err(|error| Error::File { path, error })?;
+            Some(baseline)
+        }
+        None => None,
+    };
+
+    let mut reports = Vec::with_capacity(days.len());
+    run_days(opts, days, |report| {
+        reports.push(report);
+        Ok(())
+    })?;
+
+    let rows = budget::check(&budget, baseline.as_ref(), &reports);
+    budget::write_table(&mut io::stdout().lock(), &rows).map_err(stdout_error)?;
+    Ok(rows.iter().all(Row::is_ok))
+}
+
+/// Read the file at `path`, or return `embedded` as if it was read from
+/// `name`. Returns the path along with the content.
+fn read_data(
+    path: Option<&PathBuf>,
+    name: &str,
+    embedded: &str,
+) -> Result<(String, String), Error> {
+    match path {
+        Some(path) => {
+            let path = path.display().to_string();
+            let raw = std::fs::read_to_string(&path).map_err(|error| Error::Io {
+                path: path.clone(),
+                error,
+            })?;
+            Ok((path, raw))
+        }
+        None => Ok((name.to_string(), embedded.to_string())),
+    }
+}
+
+fn stdout_error(error: io::Error) -> Error {
+    Error::Io {
+        path: "stdout".to_string(),
+        error,
+    }
 }
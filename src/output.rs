@@ -0,0 +1,446 @@
+use std::fmt::Display;
+use std::io::{self, Write};
+use std::time::Duration;
+
+use crate::allocs::{self, fmt_bytes, Allocs};
+use crate::answers::Answers;
+use crate::runner::{fmt_duration, Phase, Report};
+
+/// How the runner prints its results.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Format {
+    #[default]
+    Text,
+    Json,
+    Csv,
+    Tap,
+}
+
+impl std::str::FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            "tap" => Ok(Format::Tap),
+            _ => Err(format!("unknown format {}", s)),
+        }
+    }
+}
+
+/// Writes the reports as soon as they are available, so that a failing day
+/// doesn't swallow the results of the previous ones.
+///
+/// Every format but text emits one record per part, with the same fields:
+/// day, part, answer, number of runs, and the parse, min and median
+/// durations in milliseconds. With the `count-allocs` feature, they also
+/// have the number of allocations and the peak bytes of parsing and of the
+/// part.
+pub struct Printer<W> {
+    format: Format,
+    out: W,
+    /// a TAP test point is not ok when its answer differs from these
+    answers: Option<Answers>,
+    days: usize,
+    rows: usize,
+    failed: bool,
+    /// parts whose answer differs from the expected one
+    wrong: usize,
+    // sum of the median of parse, part 1 and part 2
+    total: [Duration; 3],
+}
+
+impl<W: Write> Printer<W> {
+    pub fn new(format: Format, out: W) -> Self {
+        Self {
+            format,
+            out,
+            answers: None,
+            days: 0,
+            rows: 0,
+            failed: false,
+            wrong: 0,
+            total: Default::default(),
+        }
+    }
+
+    pub fn with_answers(mut self, answers: Answers) -> Self {
+        self.answers = Some(answers);
+        self
+    }
+
+    /// `days` is the number of reports that will follow, TAP needs it upfront
+    pub fn start(&mut self, days: usize) -> io::Result<()> {
+        self.days = days;
+        match self.format {
+            Format::Text => Ok(()),
+            Format::Json => writeln!(self.out, "["),
+            Format::Csv => {
+                write!(self.out, "day,part,answer,runs,parse_ms,min_ms,median_ms")?;
+                if allocs::ENABLED {
+                    write!(self.out, ",{}", ALLOC_FIELDS.join(","))?;
+                }
+                writeln!(self.out)
+            }
+            Format::Tap => writeln!(self.out, "TAP version 13\n1..{}", days * 2),
+        }
+    }
+
+    pub fn report(&mut self, report: &Report) -> io::Result<()> {
+        self.total[0] += report.parse.median;
+        self.total[1] += report.part1.median;
+        self.total[2] += report.part2.median;
+
+        if self.format == Format::Text {
+            return self.text(report);
+        }
+
+        let parts = [
+            (&report.answer1, &report.part1),
+            (&report.answer2, &report.part2),
+        ];
+        for (part, (answer, phase)) in (1..).zip(parts) {
+            self.rows += 1;
+            let parse = ms(report.parse.median);
+            let (min, median) = (ms(phase.min), ms(phase.median));
+            let extra = alloc_values(report.parse.allocs, phase.allocs);
+            match self.format {
+                Format::Text => unreachable!(),
+                Format::Json => {
+                    let sep = if self.rows == 1 { "" } else { ",\n" };
+                    write!(
+                        self.out,
+                        r#"{}  {{"day": {}, "part": {}, "answer": {}, "runs": {}, "parse_ms": {}, "min_ms": {}, "median_ms": {}"#,
+                        sep,
+                        report.day,
+                        part,
+                        json_string(answer),
+                        report.runs,
+                        parse,
+                        min,
+                        median
+                    )?;
+                    for (key, value) in ALLOC_FIELDS.iter().zip(extra.iter().flatten()) {
+                        write!(self.out, ", {:?}: {}", key, value)?;
+                    }
+                    write!(self.out, "}}")?;
+                }
+                Format::Csv => {
+                    write!(
+                        self.out,
+                        "{},{},{},{},{},{},{}",
+                        report.day,
+                        part,
+                        csv_field(answer),
+                        report.runs,
+                        parse,
+                        min,
+                        median
+                    )?;
+                    if allocs::ENABLED {
+                        match extra {
+                            Some(values) => write!(self.out, ",{}", values.join(","))?,
+                            None => write!(self.out, ",,,,")?,
+                        }
+                    }
+                    writeln!(self.out)?;
+                }
+                Format::Tap => {
+                    let expected = self.answers.as_ref().and_then(|a| a.get(report.day, part));
+                    let wrong = expected.is_some_and(|e| e != answer);
+                    self.wrong += wrong as usize;
+                    writeln!(
+                        self.out,
+                        "{} {} - day{:02} part {}",
+                        if wrong { "not ok" } else { "ok" },
+                        self.rows,
+                        report.day,
+                        part
+                    )?;
+                    writeln!(self.out, "  ---")?;
+                    writeln!(self.out, "  answer: {}", json_string(answer))?;
+                    if let Some(expected) = expected.filter(|_| wrong) {
+                        writeln!(self.out, "  expected: {}", json_string(expected))?;
+                    }
+                    writeln!(self.out, "  runs: {}", report.runs)?;
+                    writeln!(self.out, "  parse_ms: {}", parse)?;
+                    writeln!(self.out, "  min_ms: {}", min)?;
+                    writeln!(self.out, "  median_ms: {}", median)?;
+                    for (key, value) in ALLOC_FIELDS.iter().zip(extra.iter().flatten()) {
+                        writeln!(self.out, "  {}: {}", key, value)?;
+                    }
+                    writeln!(self.out, "  ...")?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// whether no TAP test point was not ok
+    pub fn all_ok(&self) -> bool {
+        !self.failed && self.wrong == 0
+    }
+
+    /// The next day failed, and the run stops there. [`finish`] must still
+    /// be called, to close the JSON array.
+    ///
+    /// [`finish`]: Printer::finish
+    pub fn fail(&mut self, error: &impl Display) -> io::Result<()> {
+        self.failed = true;
+        if self.format != Format::Tap {
+            return Ok(());
+        }
+        let error = error.to_string();
+        let summary = error.lines().next().unwrap_or_default();
+        self.rows += 1;
+        writeln!(self.out, "not ok {} - {}", self.rows, summary)?;
+        writeln!(self.out, "  ---")?;
+        writeln!(self.out, "  message: {}", json_string(&error))?;
+        writeln!(self.out, "  ...")?;
+        writeln!(self.out, "Bail out! {}", summary)
+    }
+
+    pub fn finish(&mut self) -> io::Result<()> {
+        match self.format {
+            Format::Text if self.days > 1 && !self.failed => {
+                let [parse, part1, part2] = self.total;
+                writeln!(
+                    self.out,
+                    "total time: {} (parse: {}, part 1: {}, part 2: {})",
+                    fmt_duration(parse + part1 + part2),
+                    fmt_duration(parse),
+                    fmt_duration(part1),
+                    fmt_duration(part2),
+                )
+            }
+            Format::Json if self.rows == 0 => writeln!(self.out, "]"),
+            Format::Json => writeln!(self.out, "\n]"),
+            _ => Ok(()),
+        }
+    }
+
+    fn text(&mut self, report: &Report) -> io::Result<()> {
+        let tag = format!("day{:02}", report.day);
+        writeln!(self.out, "{} part 1: {}", tag, report.answer1)?;
+        writeln!(self.out, "{} part 2: {}", tag, report.answer2)?;
+
+        let fmt_phase = |phase: &Phase| {
+            if report.runs == 1 {
+                fmt_duration(phase.min)
+            } else {
+                format!(
+                    "{} (median {})",
+                    fmt_duration(phase.min),
+                    fmt_duration(phase.median)
+                )
+            }
+        };
+        writeln!(
+            self.out,
+            "{} parse: {}, part 1: {}, part 2: {}",
+            tag,
+            fmt_phase(&report.parse),
+            fmt_phase(&report.part1),
+            fmt_phase(&report.part2),
+        )?;
+
+        if let (Some(parse), Some(part1), Some(part2)) = (
+            report.parse.allocs,
+            report.part1.allocs,
+            report.part2.allocs,
+        ) {
+            let fmt_allocs =
+                |allocs: Allocs| format!("{} (peak {})", allocs.count, fmt_bytes(allocs.peak));
+            writeln!(
+                self.out,
+                "{} allocs: parse: {}, part 1: {}, part 2: {}",
+                tag,
+                fmt_allocs(parse),
+                fmt_allocs(part1),
+                fmt_allocs(part2),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+const ALLOC_FIELDS: [&str; 4] = ["parse_allocs", "parse_peak_bytes", "allocs", "peak_bytes"];
+
+/// values of the `ALLOC_FIELDS`, when the allocations were counted
+fn alloc_values(parse: Option<Allocs>, part: Option<Allocs>) -> Option<[String; 4]> {
+    let (parse, part) = (parse?, part?);
+    Some([
+        parse.count.to_string(),
+        parse.peak.to_string(),
+        part.count.to_string(),
+        part.peak.to_string(),
+    ])
+}
+
+fn ms(d: Duration) -> String {
+    format!("{:.3}", d.as_secs_f64() * 1000.0)
+}
+
+/// also valid in YAML, which is used by TAP diagnostics
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn report() -> Report {
+        let phase = |n| Phase {
+            min: Duration::from_micros(n),
+            median: Duration::from_micros(n * 2),
+            allocs: None,
+        };
+        Report {
+            day: 13,
+            answer1: "847".to_string(),
+            answer2: "\n#.#\n".to_string(),
+            runs: 3,
+            parse: phase(10),
+            part1: phase(1500),
+            part2: phase(20),
+        }
+    }
+
+    fn print(format: Format) -> String {
+        let mut printer = Printer::new(format, Vec::new());
+        printer.start(1).unwrap();
+        printer.report(&report()).unwrap();
+        printer.finish().unwrap();
+        String::from_utf8(printer.out).unwrap()
+    }
+
+    #[test]
+    fn test_format_from_str() {
+        assert_eq!(Ok(Format::Tap), "tap".parse());
+        assert!("xml".parse::<Format>().is_err());
+    }
+
+    #[test]
+    fn test_json() {
+        let expected = r#"[
+  {"day": 13, "part": 1, "answer": "847", "runs": 3, "parse_ms": 0.020, "min_ms": 1.500, "median_ms": 3.000},
+  {"day": 13, "part": 2, "answer": "\n#.#\n", "runs": 3, "parse_ms": 0.020, "min_ms": 0.020, "median_ms": 0.040}
+]
+"#;
+        assert_eq!(expected, print(Format::Json));
+    }
+
+    #[test]
+    fn test_csv() {
+        let expected = "day,part,answer,runs,parse_ms,min_ms,median_ms
+13,1,847,3,0.020,1.500,3.000
+13,2,\"
+#.#
+\",3,0.020,0.020,0.040
+";
+        let expected = if allocs::ENABLED {
+            // the report doesn't have any allocation counts
+            expected
+                .replace(
+                    "median_ms\n",
+                    "median_ms,parse_allocs,parse_peak_bytes,allocs,peak_bytes\n",
+                )
+                .replace(".000\n", ".000,,,,\n")
+                .replace(".040\n", ".040,,,,\n")
+        } else {
+            expected.to_string()
+        };
+        assert_eq!(expected, print(Format::Csv));
+    }
+
+    #[test]
+    fn test_tap() {
+        let tap = print(Format::Tap);
+        assert!(tap.starts_with("TAP version 13\n1..2\nok 1 - day13 part 1\n  ---\n"));
+        assert!(tap.contains("ok 2 - day13 part 2\n  ---\n  answer: \"\\n#.#\\n\"\n"));
+    }
+
+    #[test]
+    fn test_allocs() {
+        let mut report = report();
+        let allocs = |count, peak| Some(Allocs { count, peak });
+        report.parse.allocs = allocs(3, 100);
+        report.part1.allocs = allocs(0, 0);
+        report.part2.allocs = allocs(12, 2048);
+
+        let mut printer = Printer::new(Format::Json, Vec::new());
+        printer.report(&report).unwrap();
+        let json = String::from_utf8(printer.out).unwrap();
+        assert!(json.contains(r#""median_ms": 0.040, "parse_allocs": 3, "parse_peak_bytes": 100, "allocs": 12, "peak_bytes": 2048}"#));
+
+        let mut printer = Printer::new(Format::Text, Vec::new());
+        printer.report(&report).unwrap();
+        let text = String::from_utf8(printer.out).unwrap();
+        assert!(text.ends_with(
+            "day13 allocs: parse: 3 (peak 100B), part 1: 0 (peak 0B), part 2: 12 (peak 2.0KiB)\n"
+        ));
+    }
+
+    #[test]
+    fn test_text() {
+        let text = print(Format::Text);
+        assert!(text.starts_with("day13 part 1: 847\n"));
+        assert!(text.ends_with("day13 parse: 0.010ms (median 0.020ms), part 1: 1.500ms (median 3.000ms), part 2: 0.020ms (median 0.040ms)\n"));
+    }
+
+    #[test]
+    fn test_tap_wrong_answer() {
+        let answers = Answers::parse("[day13]\npart1 = \"846\"\n").unwrap();
+        let mut printer = Printer::new(Format::Tap, Vec::new()).with_answers(answers);
+        printer.start(1).unwrap();
+        printer.report(&report()).unwrap();
+        let tap = String::from_utf8(printer.out).unwrap();
+        assert!(tap
+            .contains("not ok 1 - day13 part 1\n  ---\n  answer: \"847\"\n  expected: \"846\"\n"));
+        // no expected answer to compare with
+        assert!(tap.contains("\nok 2 - day13 part 2\n"));
+    }
+
+    #[test]
+    fn test_fail() {
+        let fail = |format| {
+            let mut printer = Printer::new(format, Vec::new());
+            printer.start(2).unwrap();
+            printer.report(&report()).unwrap();
+            printer.fail(&"day14: invalid input\n  here").unwrap();
+            printer.finish().unwrap();
+            String::from_utf8(printer.out).unwrap()
+        };
+        let tap = fail(Format::Tap);
+        assert!(tap.ends_with(
+            "not ok 3 - day14: invalid input\n  ---\n  message: \"day14: invalid input\\n  here\"\n  ...\nBail out! day14: invalid input\n"
+        ), "{}", tap);
+        assert!(fail(Format::Json).ends_with("}\n]\n"));
+        assert!(!fail(Format::Text).contains("total time"));
+    }
+}
@@ -0,0 +1,49 @@
+//! A tiny property testing harness, checking that the alternative
+//! implementations kept around agree on random inputs.
+
+use std::fmt::Debug;
+
+pub use crate::gen::Rng;
+
+/// how many random inputs every check is run on
+const CASES: u64 = 256;
+
+/// An implementation under test, and its name for the failure messages.
+pub type Impl<'a, T, R> = (&'a str, &'a dyn Fn(&T) -> R);
+
+/// Run every implementation on inputs built by `gen`, panicking with the
+/// seed and the input as soon as one of them disagrees with the first one.
+pub fn agree<T: Debug, R: PartialEq + Debug>(
+    mut gen: impl FnMut(&mut Rng) -> T,
+    impls: &[Impl<'_, T, R>],
+) {
+    let ((reference, f), others) = impls.split_first().expect("at least one implementation");
+    for seed in 0..CASES {
+        let input = gen(&mut Rng::new(seed));
+        let expected = f(&input);
+        for (name, g) in others {
+            let actual = g(&input);
+            assert_eq!(
+                expected, actual,
+                "{} and {} disagree with seed {} on {:?}",
+                reference, name, seed, input
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "double and shift disagree with seed 0")]
+    fn test_agree() {
+        let double = |x: &u64| x * 2;
+        let shift = |x: &u64| x << 2;
+        agree(
+            |rng| rng.next_u64() % 100 + 1,
+            &[("double", &double), ("shift", &shift)],
+        );
+    }
+}
@@ -0,0 +1,294 @@
+//! Images of the grids and of the simulation steps, in formats that can be
+//! viewed without any extra library or tool: PPM, PGM and SVG.
+
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::utils::Grid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb(0, 0, 0);
+    pub const WHITE: Rgb = Rgb(255, 255, 255);
+    pub const GRAY: Rgb = Rgb(128, 128, 128);
+    pub const RED: Rgb = Rgb(220, 40, 40);
+
+    /// perceived brightness, for the gray levels of PGM
+    pub fn luma(&self) -> u8 {
+        let Rgb(r, g, b) = *self;
+        ((r as u32 * 299 + g as u32 * 587 + b as u32 * 114) / 1000) as u8
+    }
+
+    /// `t` from 0, giving `self`, to 1, giving `other`
+    pub fn mix(&self, other: Rgb, t: f32) -> Rgb {
+        let t = t.clamp(0.0, 1.0);
+        let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+        Rgb(
+            mix(self.0, other.0),
+            mix(self.1, other.1),
+            mix(self.2, other.2),
+        )
+    }
+
+    /// a few colours far enough apart to tell neighbouring areas apart
+    pub fn palette(i: usize) -> Rgb {
+        const PALETTE: [Rgb; 8] = [
+            Rgb(230, 159, 0),
+            Rgb(86, 180, 233),
+            Rgb(0, 158, 115),
+            Rgb(240, 228, 66),
+            Rgb(0, 114, 178),
+            Rgb(213, 94, 0),
+            Rgb(204, 121, 167),
+            Rgb(150, 150, 150),
+        ];
+        PALETTE[i % PALETTE.len()]
+    }
+}
+
+/// one colour per cell
+pub type Image = Grid<Rgb>;
+
+/// Anything that can be drawn as an image.
+pub trait Render {
+    fn render(&self) -> Image;
+}
+
+impl<T: Render + ?Sized> Render for &T {
+    fn render(&self) -> Image {
+        (**self).render()
+    }
+}
+
+/// lit cells in white
+impl Render for Grid<bool> {
+    fn render(&self) -> Image {
+        self.map(|lit| if *lit { Rgb::WHITE } else { Rgb::BLACK })
+    }
+}
+
+/// heights, risks or energy levels from 0 to 9
+impl Render for Grid<u8> {
+    fn render(&self) -> Image {
+        heatmap(self, 9)
+    }
+}
+
+/// from dark blue for 0 to yellow for `max`
+pub fn heatmap(grid: &Grid<u8>, max: u8) -> Image {
+    let (low, high) = (Rgb(20, 20, 90), Rgb(250, 220, 50));
+    grid.map(|v| low.mix(high, *v as f32 / max.max(1) as f32))
+}
+
+/// Draw some ASCII art, like the `Display` of the puzzles, with a colour per
+/// character. Short lines are padded with spaces.
+pub fn from_text(text: &str, colour: impl Fn(char) -> Rgb) -> Image {
+    let lines = text.lines().collect::<Vec<_>>();
+    let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+    let mut image = Grid::new(width, lines.len(), colour(' '));
+    for (y, line) in lines.iter().enumerate() {
+        for (x, c) in line.chars().enumerate() {
+            image.set(x, y, colour(c));
+        }
+    }
+    image
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    #[default]
+    Ppm,
+    Pgm,
+    Svg,
+}
+
+impl Format {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Ppm => "ppm",
+            Format::Pgm => "pgm",
+            Format::Svg => "svg",
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ppm" => Ok(Format::Ppm),
+            "pgm" => Ok(Format::Pgm),
+            "svg" => Ok(Format::Svg),
+            _ => Err(format!(
+                "unknown image format {}, expected ppm, pgm or svg",
+                s
+            )),
+        }
+    }
+}
+
+/// Write `image` with every cell drawn as a `scale` x `scale` square.
+pub fn write(out: &mut impl Write, image: &Image, format: Format, scale: usize) -> io::Result<()> {
+    let scale = scale.max(1);
+    let (width, height) = (image.width * scale, image.height * scale);
+    match format {
+        Format::Ppm | Format::Pgm => {
+            let magic = if format == Format::Ppm { "P6" } else { "P5" };
+            write!(out, "{}\n{} {}\n255\n", magic, width, height)?;
+            let mut line = Vec::with_capacity(width * 3);
+            for row in image.rows() {
+                line.clear();
+                for pixel in row {
+                    for _ in 0..scale {
+                        match format {
+                            Format::Ppm => line.extend([pixel.0, pixel.1, pixel.2]),
+                            _ => line.push(pixel.luma()),
+                        }
+                    }
+                }
+                for _ in 0..scale {
+                    out.write_all(&line)?;
+                }
+            }
+        }
+        Format::Svg => {
+            writeln!(
+                out,
+                r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}" shape-rendering="crispEdges">"#,
+                width, height, image.width, image.height
+            )?;
+            // one rectangle per run of identical cells on a row
+            for (y, row) in image.rows().enumerate() {
+                let mut x = 0;
+                for (colour, run) in runs(row) {
+                    writeln!(
+                        out,
+                        r##"<rect x="{}" y="{}" width="{}" height="1" fill="#{:02x}{:02x}{:02x}"/>"##,
+                        x, y, run, colour.0, colour.1, colour.2
+                    )?;
+                    x += run;
+                }
+            }
+            writeln!(out, "</svg>")?;
+        }
+    }
+    Ok(())
+}
+
+/// consecutive identical pixels, with how many of them there are
+fn runs(row: &[Rgb]) -> impl Iterator<Item = (Rgb, usize)> + '_ {
+    let mut rest = row;
+    std::iter::from_fn(move || {
+        let first = *rest.first()?;
+        let run = rest.iter().take_while(|p| **p == first).count();
+        rest = &rest[run..];
+        Some((first, run))
+    })
+}
+
+/// Write `image` to the file at `path`.
+pub fn save(path: &Path, image: &Image, format: Format, scale: usize) -> io::Result<()> {
+    let mut out = io::BufWriter::new(std::fs::File::create(path)?);
+    write(&mut out, image, format, scale)?;
+    out.flush()
+}
+
+/// Images written one after the other in a directory, to be assembled into
+/// an animation.
+#[derive(Debug)]
+pub struct Frames {
+    dir: PathBuf,
+    format: Format,
+    scale: usize,
+}
+
+impl Frames {
+    /// creates `dir` if needed
+    pub fn new(dir: impl Into<PathBuf>, format: Format, scale: usize) -> io::Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self { dir, format, scale })
+    }
+
+    /// Write the frame of `step` in the sequence called `name`, as
+    /// `dir/name-0042.ppm`. Returns the path of the new file.
+    pub fn save(&self, name: &str, step: usize, image: &Image) -> io::Result<PathBuf> {
+        let file = format!("{}-{:04}.{}", name, step, self.format.extension());
+        let path = self.dir.join(file);
+        save(&path, image, self.format, self.scale)?;
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn image() -> Image {
+        Grid::from_fn(3, 2, |x, y| match (x, y) {
+            (0, 0) => Rgb::WHITE,
+            (_, 1) => Rgb::RED,
+            _ => Rgb::BLACK,
+        })
+    }
+
+    #[test]
+    fn test_ppm() {
+        let mut out = Vec::new();
+        write(&mut out, &image(), Format::Ppm, 1).unwrap();
+        let mut expected = b"P6\n3 2\n255\n".to_vec();
+        expected.extend([255, 255, 255, 0, 0, 0, 0, 0, 0]);
+        expected.extend([220, 40, 40].repeat(3));
+        assert_eq!(expected, out);
+    }
+
+    #[test]
+    fn test_pgm_scaled() {
+        let mut out = Vec::new();
+        write(&mut out, &image(), Format::Pgm, 2).unwrap();
+        let header = b"P5\n6 4\n255\n";
+        assert_eq!(header, &out[..header.len()]);
+        let pixels = &out[header.len()..];
+        assert_eq!(24, pixels.len());
+        assert_eq!([255, 255, 0, 0, 0, 0], pixels[..6]);
+        assert_eq!(pixels[..6], pixels[6..12]);
+        assert_eq!(Rgb::RED.luma(), pixels[12]);
+    }
+
+    #[test]
+    fn test_svg() {
+        let mut out = Vec::new();
+        write(&mut out, &image(), Format::Svg, 10).unwrap();
+        let svg = String::from_utf8(out).unwrap();
+        assert!(svg.starts_with("<svg"), "{}", svg);
+        assert!(svg.contains(r#"width="30" height="20" viewBox="0 0 3 2""#));
+        assert!(svg.contains(r##"<rect x="1" y="0" width="2" height="1" fill="#000000"/>"##));
+        assert!(svg.contains(r##"<rect x="0" y="1" width="3" height="1" fill="#dc2828"/>"##));
+        assert_eq!(3, svg.matches("<rect").count());
+    }
+
+    #[test]
+    fn test_from_text() {
+        let image = from_text("#.\n#", |c| match c {
+            '#' => Rgb::WHITE,
+            '.' => Rgb::RED,
+            _ => Rgb::BLACK,
+        });
+        assert_eq!(
+            vec![Rgb::WHITE, Rgb::RED, Rgb::WHITE, Rgb::BLACK],
+            image.points
+        );
+    }
+
+    #[test]
+    fn test_heatmap() {
+        let grid = Grid::<u8>::from_digits("09\n").unwrap();
+        let image = grid.render();
+        assert_eq!(Some(&Rgb(20, 20, 90)), image.get(0, 0));
+        assert_eq!(Some(&Rgb(250, 220, 50)), image.get(1, 0));
+    }
+}
@@ -0,0 +1,335 @@
+use std::any::Any;
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::allocs::{self, Allocs};
+use crate::error::Error;
+use crate::trace;
+use crate::DynSolution;
+
+/// Timing of one phase over all the repetitions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Phase {
+    pub min: Duration,
+    pub median: Duration,
+    /// allocations of a single run, with the `count-allocs` feature
+    pub allocs: Option<Allocs>,
+}
+
+impl Phase {
+    /// for an even number of samples, the lower of the two middle ones is
+    /// used as the median
+    pub fn from_samples(mut samples: Vec<Duration>) -> Self {
+        samples.sort_unstable();
+        match samples.first() {
+            None => Self::default(),
+            Some(&min) => Self {
+                min,
+                median: samples[(samples.len() - 1) / 2],
+                allocs: None,
+            },
+        }
+    }
+
+    fn with_allocs(self, allocs: Option<Allocs>) -> Self {
+        Self { allocs, ..self }
+    }
+}
+
+/// time and count the allocations of `f`
+fn timed<T>(f: impl FnOnce() -> T) -> (T, Duration, Option<Allocs>) {
+    let ((result, elapsed), allocs) = allocs::measure(|| {
+        let start = Instant::now();
+        let result = f();
+        (result, start.elapsed())
+    });
+    (result, elapsed, allocs)
+}
+
+/// Answers and timings of a single day.
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub day: u8,
+    pub answer1: String,
+    pub answer2: String,
+    pub runs: usize,
+    pub parse: Phase,
+    pub part1: Phase,
+    pub part2: Phase,
+}
+
+impl Report {
+    /// sum of the median of each phase
+    pub fn median(&self) -> Duration {
+        self.parse.median + self.part1.median + self.part2.median
+    }
+
+    /// sum of the fastest run of each phase
+    pub fn min(&self) -> Duration {
+        self.parse.min + self.part1.min + self.part2.min
+    }
+}
+
+/// Parse and solve `raw` `repeat` times (at least once), timing every
+/// phase separately.
+pub fn run_day(solution: &dyn DynSolution, raw: &str, repeat: usize) -> Result<Report, Error> {
+    let runs = repeat.max(1);
+    let mut parse = Vec::with_capacity(runs);
+    let mut part1 = Vec::with_capacity(runs);
+    let mut part2 = Vec::with_capacity(runs);
+    let mut answers = (String::new(), String::new());
+    // every run allocates the same way, keep the last one
+    let mut allocs = [None; 3];
+
+    for _ in 0..runs {
+        let (input, elapsed, a) = timed(|| solution.parse(raw));
+        let input = input?;
+        parse.push(elapsed);
+        allocs[0] = a;
+
+        trace::section(format_args!("{} part1", solution.name()));
+        let (answer, elapsed, a) = timed(|| solution.part1(input.as_ref()));
+        answers.0 = answer;
+        part1.push(elapsed);
+        allocs[1] = a;
+
+        trace::section(format_args!("{} part2", solution.name()));
+        let (answer, elapsed, a) = timed(|| solution.part2(input.as_ref()));
+        answers.1 = answer;
+        part2.push(elapsed);
+        allocs[2] = a;
+    }
+
+    Ok(Report {
+        day: solution.day(),
+        answer1: answers.0,
+        answer2: answers.1,
+        runs,
+        parse: Phase::from_samples(parse).with_allocs(allocs[0]),
+        part1: Phase::from_samples(part1).with_allocs(allocs[1]),
+        part2: Phase::from_samples(part2).with_allocs(allocs[2]),
+    })
+}
+
+/// Outcome of [`run_parallel`].
+#[derive(Debug)]
+pub struct ParallelRun {
+    /// in the same order as the days given to `run_parallel`
+    pub reports: Vec<Result<Report, Error>>,
+    pub wall: Duration,
+    /// time spent in all the phases, summed over every thread
+    pub cpu: Duration,
+}
+
+type Input = Arc<dyn Any + Send + Sync>;
+
+enum Task {
+    Parse(usize),
+    Part(usize, usize, Input),
+}
+
+#[derive(Default)]
+struct Slot {
+    parse: Option<Phase>,
+    parts: [Option<(String, Phase)>; 2],
+    error: Option<Error>,
+}
+
+struct State {
+    tasks: VecDeque<Task>,
+    // queued or running tasks, the workers stop when it reaches 0
+    pending: usize,
+    slots: Vec<Slot>,
+    cpu: Duration,
+}
+
+/// Like [`run_day`] for every `(day, raw input)`, but on a pool of `threads`
+/// threads. Once a day is parsed, its two parts are run independently.
+pub fn run_parallel(
+    days: &[(&dyn DynSolution, &str)],
+    repeat: usize,
+    threads: usize,
+) -> ParallelRun {
+    let runs = repeat.max(1);
+    let state = Mutex::new(State {
+        tasks: (0..days.len()).map(Task::Parse).collect(),
+        pending: days.len(),
+        slots: days.iter().map(|_| Slot::default()).collect(),
+        cpu: Duration::default(),
+    });
+    let wakeup = Condvar::new();
+
+    let start = Instant::now();
+    std::thread::scope(|scope| {
+        for _ in 0..threads.max(1) {
+            scope.spawn(|| worker(days, runs, &state, &wakeup));
+        }
+    });
+    let wall = start.elapsed();
+
+    let state = state.into_inner().unwrap();
+    let reports = days
+        .iter()
+        .zip(state.slots)
+        .map(|((day, _), slot)| match slot {
+            Slot {
+                error: Some(error), ..
+            } => Err(error),
+            Slot {
+                parse: Some(parse),
+                parts: [Some((answer1, part1)), Some((answer2, part2))],
+                ..
+            } => Ok(Report {
+                day: day.day(),
+                answer1,
+                answer2,
+                runs,
+                parse,
+                part1,
+                part2,
+            }),
+            _ => unreachable!("every task completes before the workers stop"),
+        })
+        .collect();
+
+    ParallelRun {
+        reports,
+        wall,
+        cpu: state.cpu,
+    }
+}
+
+fn worker(days: &[(&dyn DynSolution, &str)], runs: usize, state: &Mutex<State>, wakeup: &Condvar) {
+    loop {
+        let task = {
+            let mut state = state.lock().unwrap();
+            loop {
+                if let Some(task) = state.tasks.pop_front() {
+                    break task;
+                }
+                if state.pending == 0 {
+                    return;
+                }
+                state = wakeup.wait(state).unwrap();
+            }
+        };
+        let _done = Done { state, wakeup };
+
+        match task {
+            Task::Parse(i) => {
+                let (day, raw) = days[i];
+                let mut samples = Vec::with_capacity(runs);
+                let mut input = None;
+                let mut allocs = None;
+                for _ in 0..runs {
+                    let (parsed, elapsed, a) = timed(|| day.parse(raw));
+                    input = Some(parsed);
+                    samples.push(elapsed);
+                    allocs = a;
+                    if let Some(Err(_)) = input {
+                        break;
+                    }
+                }
+
+                let mut state = state.lock().unwrap();
+                state.cpu += samples.iter().sum::<Duration>();
+                match input.unwrap() {
+                    Ok(input) => {
+                        let input: Input = Arc::from(input);
+                        state.slots[i].parse =
+                            Some(Phase::from_samples(samples).with_allocs(allocs));
+                        state.tasks.push_back(Task::Part(i, 1, input.clone()));
+                        state.tasks.push_back(Task::Part(i, 2, input));
+                        state.pending += 2;
+                    }
+                    Err(error) => state.slots[i].error = Some(error),
+                }
+            }
+            Task::Part(i, part, input) => {
+                let (day, _) = days[i];
+                let mut samples = Vec::with_capacity(runs);
+                let mut answer = String::new();
+                let mut allocs = None;
+                for _ in 0..runs {
+                    let (result, elapsed, a) = timed(|| match part {
+                        1 => day.part1(input.as_ref()),
+                        _ => day.part2(input.as_ref()),
+                    });
+                    answer = result;
+                    samples.push(elapsed);
+                    allocs = a;
+                }
+
+                let mut state = state.lock().unwrap();
+                state.cpu += samples.iter().sum::<Duration>();
+                state.slots[i].parts[part - 1] =
+                    Some((answer, Phase::from_samples(samples).with_allocs(allocs)));
+            }
+        }
+    }
+}
+
+/// Marks a task as completed when dropped, even if it panicked, so that the
+/// other workers don't wait forever.
+struct Done<'a> {
+    state: &'a Mutex<State>,
+    wakeup: &'a Condvar,
+}
+
+impl Drop for Done<'_> {
+    fn drop(&mut self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.pending -= 1;
+        drop(state);
+        self.wakeup.notify_all();
+    }
+}
+
+/// milliseconds with a microsecond precision, like `1.234ms`
+pub fn fmt_duration(d: Duration) -> String {
+    format!("{:.3}ms", d.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_phase() {
+        let ms = Duration::from_millis;
+        let phase = Phase::from_samples(vec![ms(5), ms(1), ms(3), ms(8)]);
+        assert_eq!((ms(1), ms(3)), (phase.min, phase.median));
+        assert_eq!(Phase::default(), Phase::from_samples(vec![]));
+    }
+
+    #[test]
+    fn test_run_day() {
+        let day = crate::get_day(1).unwrap();
+        let report = run_day(day, "1\n2\n3\n4\n", 3).unwrap();
+        assert_eq!((1, 3), (report.day, report.runs));
+        assert_eq!(("3", "1"), (&*report.answer1, &*report.answer2));
+        assert!(report.parse.min <= report.parse.median);
+        assert!(run_day(day, "a\n", 1).is_err());
+    }
+
+    #[test]
+    fn test_run_parallel() {
+        let day1 = crate::get_day(1).unwrap();
+        let day7 = crate::get_day(7).unwrap();
+        let days = [
+            (day1, "1\n2\n3\n4\n"),
+            (day7, "x"),
+            (day7, "16,1,2,0,4,2,7,1,2,14\n"),
+        ];
+        let run = run_parallel(&days, 2, 3);
+
+        assert_eq!(3, run.reports.len());
+        let report = run.reports[0].as_ref().unwrap();
+        assert_eq!((1, 2), (report.day, report.runs));
+        assert_eq!(("3", "1"), (&*report.answer1, &*report.answer2));
+        assert!(matches!(run.reports[1], Err(Error::Parse { day: 7, .. })));
+        let report = run.reports[2].as_ref().unwrap();
+        assert_eq!(("37", "168"), (&*report.answer1, &*report.answer2));
+    }
+}
@@ -0,0 +1,328 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+use ahash::AHashMap;
+
+/// A graph to search, given implicitly by its starting state and the
+/// successors of every state.
+pub trait SearchProblem {
+    type State: Clone + Eq + Hash;
+    type Cost: Copy + Ord + Default + Add<Output = Self::Cost>;
+
+    fn start(&self) -> Self::State;
+
+    /// call `f` with every state reachable in one step from `state`, along
+    /// with the cost of that step
+    fn successors(&self, state: &Self::State, f: impl FnMut(Self::State, Self::Cost));
+
+    fn is_goal(&self, state: &Self::State) -> bool;
+
+    /// Lower bound of the cost from `state` to the closest goal, only used by
+    /// [`astar`]. It must never overestimate the real cost, otherwise the
+    /// path found may not be the cheapest. The default is no estimate at all,
+    /// which makes `astar` equivalent to `dijkstra`.
+    fn heuristic(&self, _state: &Self::State) -> Self::Cost {
+        Self::Cost::default()
+    }
+}
+
+/// Outcome of a successful search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path<S, C> {
+    pub cost: C,
+    /// from the start to the goal, both included
+    pub states: Vec<S>,
+}
+
+/// Bookkeeping of the visited states, which are numbered in the order they
+/// are discovered.
+struct Nodes<P: SearchProblem> {
+    ids: AHashMap<P::State, usize>,
+    states: Vec<P::State>,
+    // cost from the start, and the node it was reached from
+    costs: Vec<P::Cost>,
+    parents: Vec<Option<usize>>,
+}
+
+impl<P: SearchProblem> Nodes<P> {
+    fn new(start: P::State) -> Self {
+        Self {
+            ids: AHashMap::from_iter([(start.clone(), 0)]),
+            states: vec![start],
+            costs: vec![P::Cost::default()],
+            parents: vec![None],
+        }
+    }
+
+    /// returns the id of `state` and whether it was already known
+    fn insert(&mut self, state: P::State, cost: P::Cost, parent: usize) -> (usize, bool) {
+        if let Some(&id) = self.ids.get(&state) {
+            return (id, true);
+        }
+        let id = self.states.len();
+        self.ids.insert(state.clone(), id);
+        self.states.push(state);
+        self.costs.push(cost);
+        self.parents.push(Some(parent));
+        (id, false)
+    }
+
+    fn path(&self, goal: usize) -> Path<P::State, P::Cost> {
+        let mut states = vec![self.states[goal].clone()];
+        let mut id = goal;
+        while let Some(parent) = self.parents[id] {
+            states.push(self.states[parent].clone());
+            id = parent;
+        }
+        states.reverse();
+        Path {
+            cost: self.costs[goal],
+            states,
+        }
+    }
+}
+
+/// Cheapest path from the start to a goal.
+pub fn dijkstra<P: SearchProblem>(problem: &P) -> Option<Path<P::State, P::Cost>> {
+    best_first(problem, false)
+}
+
+/// Like [`dijkstra`], but visits first the states closer to a goal according
+/// to [`SearchProblem::heuristic`].
+pub fn astar<P: SearchProblem>(problem: &P) -> Option<Path<P::State, P::Cost>> {
+    best_first(problem, true)
+}
+
+fn best_first<P: SearchProblem>(problem: &P, heuristic: bool) -> Option<Path<P::State, P::Cost>> {
+    let estimate = |state: &P::State| {
+        if heuristic {
+            problem.heuristic(state)
+        } else {
+            P::Cost::default()
+        }
+    };
+
+    let start = problem.start();
+    let mut to_visit = BinaryHeap::from([Reverse((estimate(&start), 0))]);
+    let mut nodes = Nodes::<P>::new(start);
+    let mut closed = vec![false];
+    let mut successors = Vec::new();
+
+    while let Some(Reverse((_, id))) = to_visit.pop() {
+        if closed[id] {
+            continue;
+        }
+        closed[id] = true;
+        if problem.is_goal(&nodes.states[id]) {
+            return Some(nodes.path(id));
+        }
+
+        let cost = nodes.costs[id];
+        problem.successors(&nodes.states[id], |s, c| successors.push((s, c)));
+        for (state, step) in successors.drain(..) {
+            let new_cost = cost + step;
+            let f = new_cost + estimate(&state);
+            let (next, known) = nodes.insert(state, new_cost, id);
+            if !known {
+                closed.push(false);
+            } else if new_cost < nodes.costs[next] {
+                // a heuristic that is admissible but not consistent can
+                // require visiting a state again
+                nodes.costs[next] = new_cost;
+                nodes.parents[next] = Some(id);
+                closed[next] = false;
+            } else {
+                continue;
+            }
+            to_visit.push(Reverse((f, next)));
+        }
+    }
+    None
+}
+
+/// Path to a goal with the fewest steps, regardless of their cost.
+pub fn bfs<P: SearchProblem>(problem: &P) -> Option<Path<P::State, P::Cost>> {
+    let mut nodes = Nodes::<P>::new(problem.start());
+    let mut to_visit = VecDeque::from([0]);
+    let mut successors = Vec::new();
+
+    while let Some(id) = to_visit.pop_front() {
+        if problem.is_goal(&nodes.states[id]) {
+            return Some(nodes.path(id));
+        }
+        let cost = nodes.costs[id];
+        problem.successors(&nodes.states[id], |s, c| successors.push((s, c)));
+        for (state, step) in successors.drain(..) {
+            let (next, known) = nodes.insert(state, cost + step, id);
+            if !known {
+                to_visit.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// Cheapest path to a goal, found with a depth first search remembering the
+/// cheapest cost from every state to a goal. Only suitable when the states
+/// form an acyclic graph, a state reached again while exploring its own
+/// successors is considered a dead end.
+pub fn dfs_memo<P: SearchProblem>(problem: &P) -> Option<Path<P::State, P::Cost>> {
+    // cheapest cost to a goal and next state on the way, None for dead ends
+    type Memo<S, C> = AHashMap<S, Option<(C, Option<S>)>>;
+
+    fn go<P: SearchProblem>(
+        problem: &P,
+        state: &P::State,
+        memo: &mut Memo<P::State, P::Cost>,
+    ) -> Option<P::Cost> {
+        if let Some(best) = memo.get(state) {
+            return best.as_ref().map(|(cost, _)| *cost);
+        }
+        if problem.is_goal(state) {
+            memo.insert(state.clone(), Some((P::Cost::default(), None)));
+            return Some(P::Cost::default());
+        }
+
+        memo.insert(state.clone(), None);
+        let mut successors = Vec::new();
+        problem.successors(state, |s, c| successors.push((s, c)));
+        let mut best: Option<(P::Cost, Option<P::State>)> = None;
+        for (next, step) in successors {
+            if let Some(rest) = go(problem, &next, memo) {
+                let cost = step + rest;
+                if best.as_ref().is_none_or(|(b, _)| cost < *b) {
+                    best = Some((cost, Some(next)));
+                }
+            }
+        }
+        let cost = best.as_ref().map(|(cost, _)| *cost);
+        memo.insert(state.clone(), best);
+        cost
+    }
+
+    let start = problem.start();
+    let mut memo = Memo::default();
+    let cost = go(problem, &start, &mut memo)?;
+
+    let mut states = vec![start];
+    while let Some(Some((_, Some(next)))) = memo.get(states.last().unwrap()) {
+        states.push(next.clone());
+    }
+    Some(Path { cost, states })
+}
+
+/// Number of distinct paths from the start to a goal, a path stopping at the
+/// first goal it reaches. Like [`dfs_memo`], the states must form an acyclic
+/// graph.
+pub fn count_paths<P: SearchProblem>(problem: &P) -> usize {
+    fn go<P: SearchProblem>(
+        problem: &P,
+        state: &P::State,
+        memo: &mut AHashMap<P::State, usize>,
+    ) -> usize {
+        if let Some(&count) = memo.get(state) {
+            return count;
+        }
+        if problem.is_goal(state) {
+            return 1;
+        }
+
+        memo.insert(state.clone(), 0);
+        let mut successors = Vec::new();
+        problem.successors(state, |s, _| successors.push(s));
+        let count = successors.iter().map(|s| go(problem, s, memo)).sum();
+        memo.insert(state.clone(), count);
+        count
+    }
+
+    go(problem, &problem.start(), &mut AHashMap::default())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// a small weighted DAG:
+    /// 0 -1-> 1 -1-> 3 -5-> 4
+    /// 0 -4-> 2 -1-> 4
+    /// 1 -2-> 2
+    struct Graph;
+
+    impl SearchProblem for Graph {
+        type State = u8;
+        type Cost = u32;
+
+        fn start(&self) -> u8 {
+            0
+        }
+
+        fn successors(&self, state: &u8, mut f: impl FnMut(u8, u32)) {
+            let edges: &[(u8, u32)] = match state {
+                0 => &[(1, 1), (2, 4)],
+                1 => &[(3, 1), (2, 2)],
+                2 => &[(4, 1)],
+                3 => &[(4, 5)],
+                _ => &[],
+            };
+            for &(s, c) in edges {
+                f(s, c)
+            }
+        }
+
+        fn is_goal(&self, state: &u8) -> bool {
+            *state == 4
+        }
+
+        fn heuristic(&self, state: &u8) -> u32 {
+            (*state != 4) as u32
+        }
+    }
+
+    #[test]
+    fn test_cheapest() {
+        let expected = Path {
+            cost: 4,
+            states: vec![0, 1, 2, 4],
+        };
+        assert_eq!(Some(&expected), dijkstra(&Graph).as_ref());
+        assert_eq!(Some(&expected), astar(&Graph).as_ref());
+        assert_eq!(Some(expected), dfs_memo(&Graph));
+    }
+
+    #[test]
+    fn test_bfs() {
+        let path = bfs(&Graph).unwrap();
+        assert_eq!(vec![0, 2, 4], path.states);
+        assert_eq!(5, path.cost);
+    }
+
+    #[test]
+    fn test_count_paths() {
+        assert_eq!(3, count_paths(&Graph));
+    }
+
+    #[test]
+    fn test_no_path() {
+        struct Stuck;
+        impl SearchProblem for Stuck {
+            type State = u8;
+            type Cost = u32;
+            fn start(&self) -> u8 {
+                0
+            }
+            // a cycle without any goal
+            fn successors(&self, state: &u8, mut f: impl FnMut(u8, u32)) {
+                f((state + 1) % 3, 1)
+            }
+            fn is_goal(&self, _: &u8) -> bool {
+                false
+            }
+        }
+        assert_eq!(None, dijkstra(&Stuck));
+        assert_eq!(None, bfs(&Stuck));
+        assert_eq!(None, dfs_memo(&Stuck));
+        assert_eq!(0, count_paths(&Stuck));
+    }
+}
@@ -0,0 +1,122 @@
+use std::any::Any;
+use std::fmt::Display;
+
+use crate::error::{Error, ParseError};
+use crate::input::normalize;
+use crate::render::Image;
+
+/// A single day of the advent calendar.
+pub trait Solution {
+    /// day of the month, from 1 to 25
+    const DAY: u8;
+
+    /// the puzzle input embedded at compile time, used when no other input
+    /// is provided
+    const INPUT: &'static str;
+
+    type Input;
+    type Answer1: Display;
+    type Answer2: Display;
+
+    /// `raw` is normalised by the callers below, see [`normalize`]
+    fn parse(raw: &str) -> Result<Self::Input, ParseError>;
+    fn part1(input: &Self::Input) -> Self::Answer1;
+    fn part2(input: &Self::Input) -> Self::Answer2;
+
+    /// Every problem with the shape of `raw`, without solving it. Only the
+    /// first parse error by default, days making assumptions that parsing
+    /// alone doesn't catch report all of them.
+    fn check(raw: &str) -> Vec<ParseError> {
+        Self::parse(raw).err().into_iter().collect()
+    }
+
+    /// A picture of the puzzle, for the days where seeing it helps.
+    fn render(_input: &Self::Input) -> Option<Image> {
+        None
+    }
+
+    fn solve() -> Result<(Self::Answer1, Self::Answer2), Error> {
+        Self::solve_str(Self::INPUT)
+    }
+
+    fn solve_str(raw: &str) -> Result<(Self::Answer1, Self::Answer2), Error> {
+        let input = Self::parse(&normalize(raw)).map_err(|error| Error::Parse {
+            day: Self::DAY,
+            error,
+        })?;
+        Ok((Self::part1(&input), Self::part2(&input)))
+    }
+}
+
+/// Object safe version of [`Solution`], so that every day can be stored
+/// in the same registry. Parsed inputs are type erased, and answers are
+/// rendered to strings.
+pub trait DynSolution: Sync {
+    fn day(&self) -> u8;
+    fn input(&self) -> &'static str;
+    fn parse(&self, raw: &str) -> Result<Box<dyn Any + Send + Sync>, Error>;
+    fn part1(&self, input: &(dyn Any + Send + Sync)) -> String;
+    fn part2(&self, input: &(dyn Any + Send + Sync)) -> String;
+    fn check(&self, raw: &str) -> Vec<ParseError>;
+    fn render(&self, input: &(dyn Any + Send + Sync)) -> Option<Image>;
+
+    fn name(&self) -> String {
+        format!("day{:02}", self.day())
+    }
+
+    fn solve(&self) -> Result<(String, String), Error> {
+        self.solve_str(self.input())
+    }
+
+    fn solve_str(&self, raw: &str) -> Result<(String, String), Error> {
+        let input = self.parse(raw)?;
+        Ok((self.part1(input.as_ref()), self.part2(input.as_ref())))
+    }
+}
+
+impl<S> DynSolution for S
+where
+    S: Solution + Sync,
+    S::Input: Send + Sync + 'static,
+{
+    fn day(&self) -> u8 {
+        S::DAY
+    }
+
+    fn input(&self) -> &'static str {
+        S::INPUT
+    }
+
+    fn parse(&self, raw: &str) -> Result<Box<dyn Any + Send + Sync>, Error> {
+        match S::parse(&normalize(raw)) {
+            Ok(input) => Ok(Box::new(input)),
+            Err(error) => Err(Error::Parse { day: S::DAY, error }),
+        }
+    }
+
+    fn part1(&self, input: &(dyn Any + Send + Sync)) -> String {
+        S::part1(downcast::<S>(input)).to_string()
+    }
+
+    fn part2(&self, input: &(dyn Any + Send + Sync)) -> String {
+        S::part2(downcast::<S>(input)).to_string()
+    }
+
+    fn check(&self, raw: &str) -> Vec<ParseError> {
+        S::check(&normalize(raw))
+    }
+
+    fn render(&self, input: &(dyn Any + Send + Sync)) -> Option<Image> {
+        S::render(downcast::<S>(input))
+    }
+}
+
+fn downcast<S>(input: &(dyn Any + Send + Sync)) -> &S::Input
+where
+    S: Solution,
+    S::Input: 'static,
+{
+    input
+        .downcast_ref()
+        .unwrap_or_else(|| panic!("input for day {} parsed by another day", S::DAY))
+}
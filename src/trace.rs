@@ -0,0 +1,185 @@
+//! Intermediate states of the simulation-style days, step by step, for
+//! debugging, as text and as images. Tracing is off by default, and then
+//! only costs a thread local lookup per step.
+
+use std::cell::RefCell;
+use std::fmt::Display;
+use std::io::{self, Write};
+
+use crate::render::{Frames, Render};
+
+struct Sink {
+    out: Option<Box<dyn Write>>,
+    frames: Option<Frames>,
+    /// later steps are skipped
+    max_steps: Option<usize>,
+    /// names the frames, and written as a title before the first step
+    section: String,
+    written: bool,
+    /// the first failed write, tracing stops there
+    error: Option<io::Error>,
+}
+
+thread_local! {
+    static SINK: RefCell<Option<Sink>> = const { RefCell::new(None) };
+}
+
+/// Send the traces of the current thread to `out` as text, and to `frames`
+/// as images, keeping only the first `max_steps` of every section.
+pub fn start(out: Option<Box<dyn Write>>, frames: Option<Frames>, max_steps: Option<usize>) {
+    SINK.with(|sink| {
+        *sink.borrow_mut() = Some(Sink {
+            out,
+            frames,
+            max_steps,
+            section: "trace".to_string(),
+            written: false,
+            error: None,
+        })
+    });
+}
+
+/// Stop tracing the current thread, returning the first write error.
+pub fn stop() -> io::Result<()> {
+    match SINK.with(|sink| sink.borrow_mut().take()) {
+        Some(Sink {
+            error: Some(error), ..
+        }) => Err(error),
+        Some(Sink {
+            out: Some(mut out), ..
+        }) => out.flush(),
+        Some(_) => Ok(()),
+        None => Ok(()),
+    }
+}
+
+pub fn is_enabled() -> bool {
+    SINK.with(|sink| sink.borrow().is_some())
+}
+
+/// Title of the steps that follow, like `day11 part1`. Only written if some
+/// steps are, so that the days without tracing stay silent.
+pub fn section(title: impl Display) {
+    SINK.with(|sink| {
+        if let Some(sink) = sink.borrow_mut().as_mut() {
+            sink.section = title.to_string();
+            sink.written = false;
+        }
+    });
+}
+
+/// Trace the state after `step`, 0 being the initial one. `state` is only
+/// called when tracing.
+pub fn step<D: Display>(step: usize, state: impl FnOnce() -> D) {
+    with_sink(step, |sink| write_step(sink, step, state()));
+}
+
+/// Like [`step`], for states that can also be saved as frames.
+pub fn frame<D: Display + Render>(step: usize, state: impl FnOnce() -> D) {
+    with_sink(step, |sink| {
+        let state = state();
+        write_step(sink, step, &state)?;
+        if let Some(frames) = &sink.frames {
+            let name = sink.section.replace(' ', "-");
+            frames.save(&name, step, &state.render())?;
+        }
+        Ok(())
+    });
+}
+
+/// call `f` when `step` has to be traced
+fn with_sink(step: usize, f: impl FnOnce(&mut Sink) -> io::Result<()>) {
+    SINK.with(|sink| {
+        let mut sink = sink.borrow_mut();
+        let sink = match sink.as_mut() {
+            Some(sink) if sink.error.is_none() => sink,
+            _ => return,
+        };
+        if sink.max_steps.is_some_and(|max| step > max) {
+            return;
+        }
+        if let Err(error) = f(sink) {
+            sink.error = Some(error);
+        }
+    });
+}
+
+fn write_step(sink: &mut Sink, step: usize, state: impl Display) -> io::Result<()> {
+    let out = match &mut sink.out {
+        Some(out) => out,
+        None => return Ok(()),
+    };
+    if !sink.written {
+        writeln!(out, "== {} ==", sink.section)?;
+        sink.written = true;
+    }
+    let state = state.to_string();
+    writeln!(out, "step {}", step)?;
+    out.write_all(state.as_bytes())?;
+    if !state.ends_with('\n') {
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// a writer that can still be read once given to the sink
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_trace() {
+        step(0, || -> String { panic!("not tracing") });
+
+        let out = Shared::default();
+        start(Some(Box::new(out.clone())), None, Some(1));
+        section("silent");
+        section("day00 part1");
+        for i in 0..3 {
+            step(i, || format!("state {}", i));
+        }
+        step(1, || "last\n");
+        stop().unwrap();
+        assert!(!is_enabled());
+
+        let out = String::from_utf8(out.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(
+            "== day00 part1 ==\nstep 0\nstate 0\nstep 1\nstate 1\nstep 1\nlast\n",
+            out
+        );
+    }
+
+    #[test]
+    fn test_frames() {
+        let dir = std::env::temp_dir().join(format!("aoc2021-frames-{}", std::process::id()));
+        let frames = Frames::new(&dir, crate::render::Format::Pgm, 1).unwrap();
+        start(None, Some(frames), None);
+        section("day00 part2");
+        let grid = crate::utils::Grid::<u8>::from_digits("19\n").unwrap();
+        frame(0, || &grid);
+        frame(1, || &grid);
+        stop().unwrap();
+
+        let mut files = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|f| f.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        files.sort();
+        assert_eq!(vec!["day00-part2-0000.pgm", "day00-part2-0001.pgm"], files);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
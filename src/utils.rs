@@ -1,21 +1,129 @@
-use std::{ops::Neg, str::FromStr};
+use std::str::FromStr;
 
 use nom::{
     character::complete::digit1,
-    combinator::{map, opt},
-    sequence::tuple,
+    combinator::{all_consuming, opt},
     IResult,
 };
 
+use crate::error::ParseError;
+use crate::geom::Point2;
+
+/// A rectangular grid, stored row by row.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Grid<T> {
     pub points: Vec<T>,
     pub width: usize,
     pub height: usize,
 }
 
-pub type Point = (usize, usize);
+pub type Point = Point2<usize>;
+
+const ORTHOGONAL: [(isize, isize); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+const ALL_AROUND: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
 
 impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            points: vec![value; width * height],
+            width,
+            height,
+        }
+    }
+
+    /// grid where every cell is `f(x, y)`
+    pub fn from_fn(width: usize, height: usize, mut f: impl FnMut(usize, usize) -> T) -> Self {
+        let mut points = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                points.push(f(x, y));
+            }
+        }
+        Self {
+            points,
+            width,
+            height,
+        }
+    }
+
+    /// Parse a rectangular grid, one row per line, `cell` converting every
+    /// character, or returning None when it's invalid. `expected` describes
+    /// a valid character.
+    pub fn from_str(
+        raw: &str,
+        cell: impl FnMut(char) -> Option<T>,
+        expected: &str,
+    ) -> Result<Self, ParseError> {
+        Self::from_lines(raw, raw.lines(), cell, expected)
+    }
+
+    /// Like [`Grid::from_str`], for rows which are slices of a larger input
+    /// `raw`, so that errors are located in `raw`.
+    pub fn from_lines<'a>(
+        raw: &'a str,
+        lines: impl IntoIterator<Item = &'a str>,
+        mut cell: impl FnMut(char) -> Option<T>,
+        expected: &str,
+    ) -> Result<Self, ParseError> {
+        let mut points = Vec::new();
+        let mut width: Option<usize> = None;
+        let mut height = 0;
+        for line in lines {
+            let mut row_width = 0;
+            for (i, c) in line.char_indices() {
+                match cell(c) {
+                    Some(value) => points.push(value),
+                    None => return Err(ParseError::at(raw, &line[i..], expected)),
+                }
+                row_width += 1;
+                if width.is_some_and(|w| row_width > w) {
+                    break;
+                }
+            }
+            match width {
+                Some(w) if w != row_width => {
+                    let rest = match line.char_indices().nth(w) {
+                        Some((i, _)) => &line[i..],
+                        None => &line[line.len()..],
+                    };
+                    let expected = format!("{} cells, like the first row", w);
+                    return Err(ParseError::at(raw, rest, expected));
+                }
+                _ => width = Some(row_width),
+            }
+            height += 1;
+        }
+
+        match width {
+            Some(width) if width > 0 => Ok(Self {
+                points,
+                width,
+                height,
+            }),
+            _ => Err(ParseError::at(raw, raw, format!("a grid of {}", expected))),
+        }
+    }
+
+    /// parse a rectangular grid of single digits, one row per line
+    pub fn from_digits(raw: &str) -> Result<Self, ParseError>
+    where
+        T: From<u8>,
+    {
+        Self::from_str(raw, |c| c.to_digit(10).map(|d| T::from(d as u8)), "digits")
+    }
+
     pub fn get(&self, x: usize, y: usize) -> Option<&T> {
         if x >= self.width || y >= self.height {
             None
@@ -25,26 +133,216 @@ impl<T> Grid<T> {
         }
     }
 
-    pub fn idx(&self, point: (usize, usize)) -> usize {
-        let (x, y) = point;
-        self.width * y + x
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        if x >= self.width || y >= self.height {
+            None
+        } else {
+            let idx = self.width * y + x;
+            self.points.get_mut(idx)
+        }
     }
 
-    pub fn neighbours(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
-        let mut ns = Vec::with_capacity(4);
-        if x > 0 {
-            ns.push((x - 1, y))
+    /// panics if `(x, y)` is outside of the grid
+    pub fn set(&mut self, x: usize, y: usize, value: T) {
+        match self.get_mut(x, y) {
+            Some(cell) => *cell = value,
+            None => panic!(
+                "({}, {}) is outside of a {}x{} grid",
+                x, y, self.width, self.height
+            ),
         }
-        if x < self.width - 1 {
-            ns.push((x + 1, y))
-        }
-        if y > 0 {
-            ns.push((x, y - 1))
+    }
+
+    pub fn idx(&self, Point2 { x, y }: Point) -> usize {
+        self.width * y + x
+    }
+
+    /// inverse of [`Grid::idx`]
+    pub fn point(&self, idx: usize) -> Point {
+        Point2::new(idx % self.width, idx / self.width)
+    }
+
+    /// the point `(dx, dy)` away from `(x, y)`, if it's in the grid
+    pub fn offset(&self, x: usize, y: usize, dx: isize, dy: isize) -> Option<Point> {
+        let x = x.checked_add_signed(dx).filter(|x| *x < self.width)?;
+        let y = y.checked_add_signed(dy).filter(|y| *y < self.height)?;
+        Some(Point2::new(x, y))
+    }
+
+    /// the point `(dx, dy)` away from `(x, y)`, wrapping around the edges
+    pub fn wrapping_offset(&self, x: usize, y: usize, dx: isize, dy: isize) -> Point {
+        let wrap =
+            |v: usize, d: isize, len: usize| (v as isize + d).rem_euclid(len as isize) as usize;
+        Point2::new(wrap(x, dx, self.width), wrap(y, dy, self.height))
+    }
+
+    /// the up to 4 points above, below, left and right of `(x, y)`
+    pub fn neighbours(&self, x: usize, y: usize) -> impl Iterator<Item = Point> + '_ {
+        ORTHOGONAL
+            .into_iter()
+            .filter_map(move |(dx, dy)| self.offset(x, y, dx, dy))
+    }
+
+    /// the up to 8 points around `(x, y)`, including the diagonals
+    pub fn neighbours8(&self, x: usize, y: usize) -> impl Iterator<Item = Point> + '_ {
+        ALL_AROUND
+            .into_iter()
+            .filter_map(move |(dx, dy)| self.offset(x, y, dx, dy))
+    }
+
+    /// like [`Grid::neighbours`], on a torus where the edges wrap around
+    pub fn wrapping_neighbours(&self, x: usize, y: usize) -> impl Iterator<Item = Point> + '_ {
+        ORTHOGONAL
+            .into_iter()
+            .map(move |(dx, dy)| self.wrapping_offset(x, y, dx, dy))
+    }
+
+    /// like [`Grid::neighbours8`], on a torus where the edges wrap around
+    pub fn wrapping_neighbours8(&self, x: usize, y: usize) -> impl Iterator<Item = Point> + '_ {
+        ALL_AROUND
+            .into_iter()
+            .map(move |(dx, dy)| self.wrapping_offset(x, y, dx, dy))
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.points[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> + '_ {
+        self.points.chunks(self.width)
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> + '_ {
+        self.points[x..].iter().step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T> + '_> + '_ {
+        (0..self.width).map(move |x| self.column(x))
+    }
+
+    /// every cell along with its coordinates, row by row
+    pub fn cells(&self) -> impl Iterator<Item = (Point, &T)> + '_ {
+        let width = self.width;
+        self.points
+            .iter()
+            .enumerate()
+            .map(move |(i, v)| (Point2::new(i % width, i / width), v))
+    }
+
+    pub fn cells_mut(&mut self) -> impl Iterator<Item = (Point, &mut T)> + '_ {
+        let width = self.width;
+        self.points
+            .iter_mut()
+            .enumerate()
+            .map(move |(i, v)| (Point2::new(i % width, i / width), v))
+    }
+
+    pub fn map<U>(&self, mut f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            points: self.points.iter().map(&mut f).collect(),
+            width: self.width,
+            height: self.height,
         }
-        if y < self.height - 1 {
-            ns.push((x, y + 1))
+    }
+
+    /// rows become columns
+    pub fn transpose(&self) -> Self
+    where
+        T: Clone,
+    {
+        Self::from_fn(self.height, self.width, |x, y| {
+            self[Point2::new(y, x)].clone()
+        })
+    }
+
+    /// mirror left to right
+    pub fn flip_horizontal(&self) -> Self
+    where
+        T: Clone,
+    {
+        Self::from_fn(self.width, self.height, |x, y| {
+            self[Point2::new(self.width - 1 - x, y)].clone()
+        })
+    }
+
+    /// mirror top to bottom
+    pub fn flip_vertical(&self) -> Self
+    where
+        T: Clone,
+    {
+        Self::from_fn(self.width, self.height, |x, y| {
+            self[Point2::new(x, self.height - 1 - y)].clone()
+        })
+    }
+
+    /// quarter turn clockwise
+    pub fn rotate_cw(&self) -> Self
+    where
+        T: Clone,
+    {
+        Self::from_fn(self.height, self.width, |x, y| {
+            self[Point2::new(y, self.height - 1 - x)].clone()
+        })
+    }
+
+    /// quarter turn counterclockwise
+    pub fn rotate_ccw(&self) -> Self
+    where
+        T: Clone,
+    {
+        Self::from_fn(self.height, self.width, |x, y| {
+            self[Point2::new(self.width - 1 - y, x)].clone()
+        })
+    }
+
+    /// the `width` x `height` part of the grid starting at `(x, y)`, if it
+    /// fits in the grid
+    pub fn subgrid(&self, x: usize, y: usize, width: usize, height: usize) -> Option<Self>
+    where
+        T: Clone,
+    {
+        if x + width > self.width || y + height > self.height {
+            return None;
         }
-        ns.into_iter()
+        Some(Self::from_fn(width, height, |dx, dy| {
+            self[Point2::new(x + dx, y + dy)].clone()
+        }))
+    }
+
+    /// Repeat the grid `nx` times to the right and `ny` times down, `f`
+    /// computing the value of each copied cell from the original one and the
+    /// coordinates of its tile.
+    pub fn tile(&self, nx: usize, ny: usize, mut f: impl FnMut(&T, usize, usize) -> T) -> Self {
+        Self::from_fn(self.width * nx, self.height * ny, |x, y| {
+            let cell = &self[Point2::new(x % self.width, y % self.height)];
+            f(cell, x / self.width, y / self.height)
+        })
+    }
+}
+
+impl<T> std::ops::Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, Point2 { x, y }: Point) -> &T {
+        assert!(
+            x < self.width && y < self.height,
+            "({}, {}) is outside of the grid",
+            x,
+            y
+        );
+        &self.points[self.width * y + x]
+    }
+}
+
+impl<T> std::ops::IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, Point2 { x, y }: Point) -> &mut T {
+        assert!(
+            x < self.width && y < self.height,
+            "({}, {}) is outside of the grid",
+            x,
+            y
+        );
+        &mut self.points[self.width * y + x]
     }
 }
 
@@ -53,9 +351,9 @@ where
     T: std::fmt::Display,
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for y in 0..self.height {
-            for x in 0..self.width {
-                f.write_fmt(format_args!("{}", self.get(x, y).unwrap()))?;
+        for row in self.rows() {
+            for cell in row {
+                f.write_fmt(format_args!("{}", cell))?;
             }
             f.write_str("\n")?;
         }
@@ -67,20 +365,245 @@ pub fn parse_i32(raw: &str) -> IResult<&str, i32> {
     parse_signed::<i32>(raw)
 }
 
-/// parse a signed digit
-pub fn parse_signed<F>(raw: &str) -> IResult<&str, F>
+/// Parse a signed number. Digits out of range are a failure rather than an
+/// error, so that alternatives don't hide it, and it points at them.
+pub fn parse_signed<F: FromStr>(raw: &str) -> IResult<&str, F> {
+    let (raw, sign) = opt(nom::character::complete::char('-'))(raw)?;
+    let (rest, ds) = digit1(raw)?;
+    let n = match sign {
+        None => ds.parse(),
+        Some(_) => format!("-{}", ds).parse(),
+    };
+    let failure = || nom::error::Error::new(raw, nom::error::ErrorKind::MapRes);
+    Ok((rest, n.map_err(|_| nom::Err::Failure(failure()))?))
+}
+
+/// run `parser` on every line of `raw`, each line must be entirely consumed.
+/// `expected` describes what a line should look like, for error reporting.
+pub fn parse_lines<'a, T, P>(
+    raw: &'a str,
+    mut parser: P,
+    expected: &str,
+) -> Result<Vec<T>, ParseError>
 where
-    F: FromStr + Neg<Output = F>,
-    <F as FromStr>::Err: std::fmt::Debug,
+    P: nom::Parser<&'a str, T, nom::error::Error<&'a str>>,
 {
-    map(
-        tuple((opt(nom::character::complete::char('-')), digit1)),
-        |(sign, ds): (Option<char>, &str)| {
-            let x: F = ds.parse().unwrap();
-            match sign {
-                None => x,
-                Some(_) => x.neg(),
+    raw.lines()
+        .map(|line| {
+            all_consuming(|i| parser.parse(i))(line)
+                .map(|(_, x)| x)
+                .map_err(|err| ParseError::from_nom(raw, err, expected))
+        })
+        .collect()
+}
+
+/// like [`parse_lines`], but carrying on after a bad line, to report all of
+/// them
+pub fn check_lines<'a, T, P>(raw: &'a str, mut parser: P, expected: &str) -> Vec<ParseError>
+where
+    P: nom::Parser<&'a str, T, nom::error::Error<&'a str>>,
+{
+    raw.lines()
+        .filter_map(|line| all_consuming(|i| parser.parse(i))(line).err())
+        .map(|err| ParseError::from_nom(raw, err, expected))
+        .collect()
+}
+
+/// Every bad cell of a grid made of `lines`, along with the rows and columns
+/// too many or missing. The grid must be `width` x `height` when `size` is
+/// given, and as wide as its first row otherwise.
+pub fn check_grid<'a>(
+    raw: &'a str,
+    lines: impl IntoIterator<Item = &'a str>,
+    size: Option<(usize, usize)>,
+    is_cell: impl Fn(char) -> bool,
+    expected: &str,
+) -> Vec<ParseError> {
+    let lines = lines.into_iter().collect::<Vec<_>>();
+    let mut errors = Vec::new();
+    let mut width = size.map(|(w, _)| w);
+    for line in &lines {
+        for (i, c) in line.char_indices() {
+            if !is_cell(c) {
+                errors.push(ParseError::at(raw, &line[i..], expected));
+            }
+        }
+        let row_width = line.chars().count();
+        match width {
+            Some(w) if w != row_width => {
+                let rest = match line.char_indices().nth(w) {
+                    Some((i, _)) => &line[i..],
+                    None => &line[line.len()..],
+                };
+                errors.push(ParseError::at(raw, rest, format!("{} cells per row", w)));
             }
-        },
-    )(raw)
+            _ => width = Some(row_width),
+        }
+    }
+
+    match (size, lines.last()) {
+        (Some((_, h)), _) if lines.len() > h => {
+            errors.push(ParseError::at(raw, lines[h], format!("{} rows", h)));
+        }
+        (Some((_, h)), Some(last)) if lines.len() < h => {
+            errors.push(ParseError::at(
+                raw,
+                &last[last.len()..],
+                format!("{} rows", h),
+            ));
+        }
+        (_, None) => errors.push(ParseError::eof(raw, expected)),
+        _ => (),
+    }
+    errors
+}
+
+/// run `parser` on the whole `raw` input, converting nom errors
+pub fn parse_all<'a, T, P>(raw: &'a str, parser: P, expected: &str) -> Result<T, ParseError>
+where
+    P: nom::Parser<&'a str, T, nom::error::Error<&'a str>>,
+{
+    all_consuming(parser)(raw)
+        .map(|(_, x)| x)
+        .map_err(|err| ParseError::from_nom(raw, err, expected))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn grid() -> Grid<u8> {
+        // 1 2 3
+        // 4 5 6
+        Grid::from_digits("123\n456\n").unwrap()
+    }
+
+    #[test]
+    fn test_parse_signed() {
+        assert_eq!(Ok(("", i32::MIN)), parse_signed::<i32>("-2147483648"));
+        assert_eq!(Ok((",", 12)), parse_signed::<u8>("12,"));
+        let raw = "x=-99999999999";
+        let err = parse_lines(
+            raw,
+            |i| nom::sequence::preceded(nom::bytes::complete::tag("x="), parse_i32)(i),
+            "a range",
+        )
+        .unwrap_err();
+        assert_eq!(4, err.column);
+        assert!(parse_signed::<u8>("256").is_err());
+    }
+
+    #[test]
+    fn test_from_str() {
+        let g = Grid::from_str(
+            "#.\n.#\n",
+            |c| match c {
+                '#' => Some(1),
+                '.' => Some(0),
+                _ => None,
+            },
+            "`#` or `.`",
+        )
+        .unwrap();
+        assert_eq!((2, 2), (g.width, g.height));
+        assert_eq!(vec![1, 0, 0, 1], g.points);
+
+        let err = Grid::<u8>::from_digits("12\n1x\n").unwrap_err();
+        assert_eq!((2, 2), (err.line, err.column));
+        let err = Grid::<u8>::from_digits("12\n123\n").unwrap_err();
+        assert_eq!((2, 3), (err.line, err.column));
+        let err = Grid::<u8>::from_digits("12\n1\n").unwrap_err();
+        assert_eq!((2, 2), (err.line, err.column));
+        assert!(Grid::<u8>::from_digits("").is_err());
+    }
+
+    #[test]
+    fn test_get_set() {
+        let mut g = grid();
+        assert_eq!(Some(&6), g.get(2, 1));
+        assert_eq!(None, g.get(3, 0));
+        g.set(0, 1, 9);
+        *g.get_mut(1, 0).unwrap() = 8;
+        g[Point2::new(2, 0)] = 7;
+        assert_eq!(vec![1, 8, 7, 9, 5, 6], g.points);
+        let p = Point2::new(2, 1);
+        assert_eq!(p, g.point(g.idx(p)));
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let g = grid();
+        let sorted = |it: &mut dyn Iterator<Item = Point>| {
+            let mut v = it.map(|p| (p.x, p.y)).collect::<Vec<_>>();
+            v.sort_unstable();
+            v
+        };
+        assert_eq!(vec![(0, 1), (1, 0)], sorted(&mut g.neighbours(0, 0)));
+        assert_eq!(
+            vec![(0, 0), (0, 1), (1, 1), (2, 0), (2, 1)],
+            sorted(&mut g.neighbours8(1, 0))
+        );
+        assert_eq!(
+            // only 2 rows, so above and below are the same
+            vec![(0, 1), (0, 1), (1, 0), (2, 0)],
+            sorted(&mut g.wrapping_neighbours(0, 0))
+        );
+        assert_eq!(8, g.wrapping_neighbours8(0, 0).count());
+        assert_eq!(Point2::new(2, 1), g.wrapping_offset(0, 0, -1, -1));
+    }
+
+    #[test]
+    fn test_iterators() {
+        let g = grid();
+        assert_eq!(
+            vec![&[1, 2, 3][..], &[4, 5, 6]],
+            g.rows().collect::<Vec<_>>()
+        );
+        assert_eq!(vec![&2, &5], g.column(1).collect::<Vec<_>>());
+        assert_eq!(3, g.columns().count());
+        assert_eq!(Some((Point2::new(1, 1), &5)), g.cells().nth(4));
+        assert_eq!("123\n456\n", g.to_string());
+    }
+
+    #[test]
+    fn test_transforms() {
+        let g = grid();
+        assert_eq!("14\n25\n36\n", g.transpose().to_string());
+        assert_eq!("321\n654\n", g.flip_horizontal().to_string());
+        assert_eq!("456\n123\n", g.flip_vertical().to_string());
+        assert_eq!("41\n52\n63\n", g.rotate_cw().to_string());
+        assert_eq!("36\n25\n14\n", g.rotate_ccw().to_string());
+        assert_eq!(g, g.rotate_cw().rotate_cw().rotate_cw().rotate_cw());
+        assert_eq!("23\n56\n", g.subgrid(1, 0, 2, 2).unwrap().to_string());
+        assert_eq!(None, g.subgrid(2, 0, 2, 2));
+        assert_eq!(
+            "123234\n456567\n",
+            g.tile(2, 1, |v, tx, _| v + tx as u8).to_string()
+        );
+    }
+
+    #[test]
+    fn test_check_grid() {
+        let raw = "12\n3x4\n5y\n";
+        let errors = check_grid(
+            raw,
+            raw.lines(),
+            Some((2, 2)),
+            |c| c.is_ascii_digit(),
+            "a digit",
+        )
+        .into_iter()
+        .map(|e| (e.line, e.column, e.expected))
+        .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                (2, 2, "a digit".to_string()),
+                (2, 3, "2 cells per row".to_string()),
+                (3, 2, "a digit".to_string()),
+                (3, 1, "2 rows".to_string()),
+            ],
+            errors
+        );
+        assert!(check_grid("12\n34\n", "12\n34\n".lines(), None, |_| true, "").is_empty());
+    }
 }
@@ -0,0 +1,18 @@
+use aoc2021::answers::{self, Answers};
+use aoc2021::{runner, DAYS};
+
+// every day, with the real inputs, must still give the recorded answers
+#[test]
+fn test_answers() {
+    let answers = Answers::parse(answers::EMBEDDED).unwrap();
+    let mismatches = DAYS
+        .iter()
+        .flat_map(|day| {
+            let report = runner::run_day(*day, day.input(), 1).unwrap();
+            answers.check(&report)
+        })
+        .map(|mismatch| mismatch.to_string())
+        .collect::<Vec<_>>();
+
+    assert!(mismatches.is_empty(), "{}", mismatches.join("\n"));
+}
@@ -0,0 +1,95 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use aoc2021::answers::Answers;
+use aoc2021::get_day;
+
+type Mangle = fn(&str) -> String;
+
+/// How an input may have been mangled when saved on another machine. Every
+/// example must give the same answers in all these forms.
+const VARIANTS: [(&str, Mangle); 4] = [
+    ("as is", |raw| raw.to_string()),
+    ("crlf", |raw| raw.replace('\n', "\r\n")),
+    ("bom", |raw| format!("\u{feff}{}", raw)),
+    ("trailing whitespace", |raw| {
+        format!("{}\n\n", raw.replace('\n', " \t\n"))
+    }),
+];
+
+// Every resources/examples/dayNN/NAME.txt must give the answers found in
+// NAME.toml, in the format of answers.toml. A part without an answer isn't
+// run, some examples only make sense for one of them.
+#[test]
+fn test_examples() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/examples");
+    let mut checked = 0;
+    let mut failures = Vec::new();
+    for (day, input) in examples(&root) {
+        let solution = get_day(day).unwrap_or_else(|| panic!("no day {}", day));
+        let answers = input.with_extension("toml");
+        let answers = fs::read_to_string(&answers)
+            .map_err(|e| e.to_string())
+            .and_then(|raw| Answers::parse(&raw).map_err(|e| e.to_string()))
+            .unwrap_or_else(|e| panic!("{}: {}", answers.display(), e));
+        let parts = [1, 2]
+            .into_iter()
+            .filter_map(|part| Some((part, answers.get(day, part)?)))
+            .collect::<Vec<_>>();
+        if parts.is_empty() {
+            failures.push(format!("{}: no answer for day{:02}", input.display(), day));
+        }
+
+        let raw = fs::read_to_string(&input).unwrap();
+        for (variant, mangle) in VARIANTS {
+            let name = format!("{} ({})", input.display(), variant);
+            let mangled = mangle(&raw);
+            for error in solution.check(&mangled) {
+                failures.push(format!("{}: {}", name, error));
+            }
+            let parsed = match solution.parse(&mangled) {
+                Ok(parsed) => parsed,
+                Err(err) => {
+                    failures.push(format!("{}: {}", name, err));
+                    continue;
+                }
+            };
+            for &(part, expected) in &parts {
+                checked += 1;
+                let actual = match part {
+                    1 => solution.part1(parsed.as_ref()),
+                    _ => solution.part2(parsed.as_ref()),
+                };
+                if actual != expected {
+                    failures.push(format!(
+                        "{} part {}: expected {:?}, got {:?}",
+                        name, part, expected, actual
+                    ));
+                }
+            }
+        }
+    }
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+    assert!(checked > 0, "no examples in {}", root.display());
+}
+
+/// the example inputs, with their day, in a stable order
+fn examples(root: &Path) -> Vec<(u8, PathBuf)> {
+    let mut examples = Vec::new();
+    for dir in fs::read_dir(root).unwrap() {
+        let dir = dir.unwrap().path();
+        let day = dir
+            .file_name()
+            .and_then(|name| name.to_str()?.strip_prefix("day")?.parse().ok())
+            .unwrap_or_else(|| panic!("{} should be named dayNN", dir.display()));
+        for file in fs::read_dir(&dir).unwrap() {
+            let file = file.unwrap().path();
+            if file.extension() == Some("txt".as_ref()) {
+                examples.push((day, file));
+            }
+        }
+    }
+    examples.sort();
+    examples
+}